@@ -9,7 +9,7 @@
 use crate::common_multipart::client::multipart;
 use actix_http::body::{BodySize, MessageBody};
 use bytes::Bytes;
-use futures_core::{ready, Stream};
+use futures_core::ready;
 use std::{
     pin::Pin,
     task::{Context, Poll},
@@ -17,8 +17,20 @@ use std::{
 
 /// Wraps a
 /// [`common_multipart::client::multipart::Body`] and makes it compatible with Actix.
+///
+/// Actix bodies can't send trailers, so trailers of the form, including
+/// `Checksum::trailer` digests, aren't sent. The digests can be read with
+/// `Body::checksums` once the body is written.
 pub struct Body<'a>(multipart::Body<'a>);
 
+impl<'a> Body<'a> {
+    /// Returns the checksums that have been computed over the parts written
+    /// so far.
+    pub fn checksums(&self) -> &[multipart::PartChecksum] {
+        self.0.checksums()
+    }
+}
+
 impl<'a> From<multipart::Form<'a>> for Body<'a> {
     fn from(form: multipart::Form<'a>) -> Body<'a> {
         Body(multipart::Body::from(form))
@@ -38,8 +50,18 @@ impl<'a> MessageBody for Body<'a> {
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
         let Body(ref mut inner) = Pin::into_inner(self);
 
-        match ready!(Stream::poll_next(Pin::new(inner), cx)) {
-            Some(Ok(bytes)) => Poll::Ready(Some(Ok(bytes.freeze()))),
+        #[cfg(feature = "tracing")]
+        let span = inner.span().clone();
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        match ready!(inner.poll_bytes(cx)) {
+            Some(Ok(bytes)) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(len = bytes.len(), "chunk");
+
+                Poll::Ready(Some(Ok(bytes)))
+            }
             Some(Err(err)) => Poll::Ready(Some(Err(err))),
             None => Poll::Ready(None),
         }
@@ -0,0 +1,145 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::error::PartContext;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::Waker,
+};
+
+/// Stops a body from being written. The body returns `Error::Aborted` with
+/// a report of how far it got, and drops its readers, and open files.
+///
+/// The handle can be cloned, and sent to another task, like the one that
+/// handles a user cancelling an upload.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart;
+///
+/// let mut form = multipart::Form::default();
+///
+/// form.add_text("text", "Hello World!");
+///
+/// let handle = form.abort_handle();
+///
+/// // Later, from anywhere:
+/// handle.abort();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AbortHandle {
+    inner: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    aborted: AtomicBool,
+
+    /// The task of the body, which is woken to return the error.
+    waker: Mutex<Option<Waker>>,
+
+    /// The report of a body that was aborted, or dropped before it was
+    /// fully written.
+    report: Mutex<Option<AbortReport>>,
+}
+
+impl AbortHandle {
+    /// Aborts the body. The body stops the next time it is polled, even if
+    /// it is waiting on a reader.
+    pub fn abort(&self) {
+        self.inner.aborted.store(true, Ordering::Release);
+
+        let waker = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if `abort` has been called.
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::Acquire)
+    }
+
+    /// Returns how far the body got, once it has been aborted, or dropped
+    /// before it was fully written.
+    pub fn report(&self) -> Option<AbortReport> {
+        self.inner
+            .report
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Remembers the task to wake if the body is aborted.
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut current = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match *current {
+            Some(ref current) if current.will_wake(waker) => (),
+            _ => *current = Some(waker.clone()),
+        }
+    }
+
+    pub(crate) fn set_report(&self, report: AbortReport) {
+        *self
+            .inner
+            .report
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(report);
+    }
+}
+
+/// How far a body got before it was stopped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AbortReport {
+    pub(crate) sent_parts: Vec<PartContext>,
+
+    pub(crate) interrupted: Option<PartContext>,
+
+    pub(crate) bytes_written: u64,
+}
+
+impl AbortReport {
+    /// Returns the parts that were fully written, in order. Parts can share
+    /// a name, like `files[]`, so each has its index in the body, and the
+    /// length of its content as its offset.
+    pub fn sent_parts(&self) -> &[PartContext] {
+        &self.sent_parts
+    }
+
+    /// Returns the part that was being written, if any.
+    pub fn interrupted_part(&self) -> Option<&PartContext> {
+        self.interrupted.as_ref()
+    }
+
+    /// Returns the number of bytes of the interrupted part's content that
+    /// were written, after any compression, and transfer encoding. A chunk
+    /// that was waiting on a rate limiter is counted as written.
+    pub fn interrupted_offset(&self) -> Option<u64> {
+        self.interrupted.as_ref().map(PartContext::offset)
+    }
+
+    /// Returns the number of bytes of the body that were written.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Batches of HTTP requests sent as a single multipart/mixed request, like
+//! OData, and Google batch requests.
+
+use crate::{
+    client_::{Form, Part},
+    error::Error,
+    parser::{self, ParsedPart},
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{
+    header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, HOST},
+    Request, Response, StatusCode, Version,
+};
+use std::io::Cursor;
+
+static CONTENT_ID: HeaderName = HeaderName::from_static("content-id");
+
+/// A batch of requests, each sent as an `application/http` part with a
+/// Content-ID used to match it to its response.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+/// use common_multipart_rfc7578::client::multipart::Batch;
+/// use http::Request;
+///
+/// let mut batch = Batch::new();
+///
+/// batch.add_request(Request::get("https://example.com/users/1").body(Bytes::new()).unwrap());
+/// batch.add_request(
+///     Request::post("https://example.com/users")
+///         .header("content-type", "application/json")
+///         .body(Bytes::from("{\"name\":\"Ferris\"}"))
+///         .unwrap(),
+/// );
+///
+/// let form = batch.form();
+///
+/// assert!(form.content_type().starts_with("multipart/mixed"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Batch {
+    /// The Content-ID, and serialized message of each request.
+    requests: Vec<(String, Bytes)>,
+}
+
+impl Batch {
+    /// Creates an empty batch.
+    pub fn new() -> Batch {
+        Batch::default()
+    }
+
+    /// Adds a request to the batch, with a Content-ID of its position in the
+    /// batch, starting from 1, or the next number that isn't used already.
+    /// Returns the Content-ID.
+    pub fn add_request(&mut self, req: Request<Bytes>) -> String {
+        let content_id = (self.requests.len() + 1..)
+            .map(|id| id.to_string())
+            .find(|id| !self.has_content_id(id))
+            .unwrap();
+
+        self.requests
+            .push((content_id.clone(), serialize_request(req)));
+
+        content_id
+    }
+
+    /// Adds a request to the batch with the given Content-ID. Fails if the
+    /// Content-ID is empty, has characters other than visible ASCII, or
+    /// angle brackets, which it is written in, or is already used in the
+    /// batch.
+    pub fn add_request_with_id<I>(
+        &mut self,
+        content_id: I,
+        req: Request<Bytes>,
+    ) -> Result<(), Error>
+    where
+        I: Into<String>,
+    {
+        let content_id = content_id.into();
+        let valid = !content_id.is_empty()
+            && content_id
+                .bytes()
+                .all(|b| b.is_ascii_graphic() && b != b'<' && b != b'>');
+
+        if !valid || self.has_content_id(&content_id) {
+            return Err(Error::InvalidContentId { content_id });
+        }
+
+        self.requests.push((content_id, serialize_request(req)));
+
+        Ok(())
+    }
+
+    fn has_content_id(&self, content_id: &str) -> bool {
+        self.requests.iter().any(|(id, _)| id == content_id)
+    }
+
+    /// Returns the number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if the batch doesn't have any requests.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Builds the multipart/mixed form of the batch.
+    pub fn form(&self) -> Form<'static> {
+        let mut form = Form::default();
+
+        form.set_subtype("mixed");
+        for (content_id, message) in self.requests.iter() {
+            // Content-IDs are written in angle brackets, like a message ID,
+            // and are checked to be visible ASCII when they are added.
+            let value = HeaderValue::from_str(&format!("<{}>", content_id)).unwrap();
+
+            form.add_part(
+                Part::reader(content_id, Cursor::new(message.clone()))
+                    .with_mime("application/http".parse().unwrap())
+                    .with_header(CONTENT_ID.clone(), value)
+                    .with_header(
+                        HeaderName::from_static("content-transfer-encoding"),
+                        HeaderValue::from_static("binary"),
+                    ),
+            );
+        }
+
+        form
+    }
+
+    /// Splits a batch response into the response of each request, in the
+    /// order the requests were added. Responses are matched by their
+    /// Content-ID, which may be prefixed with `response-`, or by position if
+    /// they don't have one. A request without a response is `None`, and a
+    /// request with more than one response is an error.
+    ///
+    /// Responses nested in a multipart/mixed part, like an OData change set,
+    /// are matched the same way.
+    pub fn parse_response(
+        &self,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Vec<Option<Response<Bytes>>>, Error> {
+        let mut parts = vec![];
+
+        flatten_parts(content_type, &body, &mut parts)?;
+
+        let mut responses: Vec<Option<Response<Bytes>>> =
+            self.requests.iter().map(|_| None).collect();
+
+        for (position, part) in parts.into_iter().enumerate() {
+            let index = match part.headers.get(&CONTENT_ID) {
+                Some(content_id) => {
+                    let content_id = content_id
+                        .to_str()
+                        .map_err(|_| Error::Parse("invalid Content-ID".to_string()))?;
+                    let content_id = content_id
+                        .trim()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>');
+                    let content_id = content_id.strip_prefix("response-").unwrap_or(content_id);
+
+                    self.requests
+                        .iter()
+                        .position(|(id, _)| id == content_id)
+                        .ok_or_else(|| {
+                            Error::Parse(format!("unknown Content-ID {:?}", content_id))
+                        })?
+                }
+                None if position < self.requests.len() => position,
+                None => return Err(Error::Parse("more responses than requests".to_string())),
+            };
+
+            if responses[index].is_some() {
+                return Err(Error::Parse(format!(
+                    "duplicate response to Content-ID {:?}",
+                    self.requests[index].0
+                )));
+            }
+            responses[index] = Some(parse_response(part.body)?);
+        }
+
+        Ok(responses)
+    }
+}
+
+/// Collects the parts of a multipart body, replacing nested multipart parts
+/// with their own parts.
+fn flatten_parts(content_type: &str, body: &Bytes, out: &mut Vec<ParsedPart>) -> Result<(), Error> {
+    let boundary = parser::boundary(content_type)?;
+
+    for part in parser::parse(body, &boundary)? {
+        let nested = part
+            .headers
+            .get(&CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| {
+                value
+                    .trim_start()
+                    .get(..10)
+                    .is_some_and(|ty| ty.eq_ignore_ascii_case("multipart/"))
+            })
+            .map(str::to_string);
+
+        match nested {
+            Some(content_type) => flatten_parts(&content_type, &part.body, out)?,
+            None => out.push(part),
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a request as an HTTP/1.1 message.
+fn serialize_request(req: Request<Bytes>) -> Bytes {
+    let (head, body) = req.into_parts();
+    let target = head
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    let mut buf = BytesMut::new();
+
+    buf.put_slice(head.method.as_str().as_bytes());
+    buf.put_u8(b' ');
+    buf.put_slice(target.as_bytes());
+    buf.put_slice(b" HTTP/1.1\r\n");
+    if let (Some(authority), false) = (head.uri.authority(), head.headers.contains_key(HOST)) {
+        write_header(&mut buf, &HOST, authority.as_str().as_bytes());
+    }
+    for (name, value) in head.headers.iter() {
+        write_header(&mut buf, name, value.as_bytes());
+    }
+    if !body.is_empty() && !head.headers.contains_key(CONTENT_LENGTH) {
+        write_header(&mut buf, &CONTENT_LENGTH, body.len().to_string().as_bytes());
+    }
+    buf.put_slice(b"\r\n");
+    buf.put_slice(&body);
+
+    buf.freeze()
+}
+
+fn write_header(buf: &mut BytesMut, name: &HeaderName, value: &[u8]) {
+    buf.put_slice(name.as_str().as_bytes());
+    buf.put_slice(b": ");
+    buf.put_slice(value);
+    buf.put_slice(b"\r\n");
+}
+
+/// Parses an HTTP/1.x response message.
+fn parse_response(message: Bytes) -> Result<Response<Bytes>, Error> {
+    let invalid = || Error::Parse("invalid response status line".to_string());
+
+    let line_end = message
+        .windows(2)
+        .position(|window| window == b"\r\n")
+        .ok_or_else(invalid)?;
+    let line = std::str::from_utf8(&message[..line_end]).map_err(|_| invalid())?;
+    let mut fields = line.splitn(3, ' ');
+    let version = match fields.next() {
+        Some("HTTP/1.0") => Version::HTTP_10,
+        Some("HTTP/1.1") => Version::HTTP_11,
+        _ => return Err(invalid()),
+    };
+    let status = fields
+        .next()
+        .and_then(|code| StatusCode::from_bytes(code.as_bytes()).ok())
+        .ok_or_else(invalid)?;
+
+    let rest = message.slice(line_end + 2..);
+    let (head, body) = if rest.starts_with(b"\r\n") {
+        (Bytes::new(), rest.slice(2..))
+    } else {
+        let end = rest
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or_else(|| Error::Parse("unterminated response headers".to_string()))?;
+
+        (rest.slice(..end), rest.slice(end + 4..))
+    };
+    let headers = parser::parse_headers(&head)?;
+
+    // Drop any line break between the body and the next boundary that is
+    // not part of the body.
+    let body = match headers
+        .get(&CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok())
+    {
+        Some(len) if len <= body.len() => body.slice(..len),
+        _ => body,
+    };
+
+    let mut response = Response::new(body);
+
+    *response.status_mut() = status;
+    *response.version_mut() = version;
+    *response.headers_mut() = headers;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Batch;
+    use crate::client_::Body;
+    use bytes::Bytes;
+    use futures_util::TryStreamExt;
+    use http::{Request, StatusCode};
+
+    #[tokio::test]
+    async fn form_writes_requests() {
+        let mut batch = Batch::new();
+
+        batch.add_request(
+            Request::post("https://example.com/users?notify=true")
+                .header("content-type", "application/json")
+                .body(Bytes::from("{}"))
+                .unwrap(),
+        );
+
+        let form = batch.form();
+        let body = Body::from(form).try_concat().await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+
+        assert!(body.contains(
+            "content-type: application/http\r\n\
+             content-id: <1>\r\n\
+             content-transfer-encoding: binary\r\n\
+             \r\n\
+             POST /users?notify=true HTTP/1.1\r\n\
+             host: example.com\r\n\
+             content-type: application/json\r\n\
+             content-length: 2\r\n\
+             \r\n\
+             {}\r\n"
+        ));
+        assert!(!body.contains("content-disposition"));
+    }
+
+    #[test]
+    fn parse_response_matches_content_ids() {
+        let mut batch = Batch::new();
+
+        for _ in 0..3 {
+            batch.add_request(Request::get("/").body(Bytes::new()).unwrap());
+        }
+
+        let body = "--batch\r\n\
+            content-type: application/http\r\n\
+            content-id: <response-2>\r\n\
+            \r\n\
+            HTTP/1.1 404 Not Found\r\n\
+            content-length: 9\r\n\
+            \r\n\
+            not found\r\n\
+            --batch\r\n\
+            content-type: multipart/mixed; boundary=changeset\r\n\
+            \r\n\
+            --changeset\r\n\
+            content-type: application/http\r\n\
+            content-id: 1\r\n\
+            \r\n\
+            HTTP/1.1 204 No Content\r\n\
+            \r\n\
+            \r\n\
+            --changeset--\r\n\
+            --batch--\r\n";
+
+        let responses = batch
+            .parse_response("multipart/mixed; boundary=batch", body.into())
+            .unwrap();
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0].as_ref().unwrap().status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            responses[1].as_ref().unwrap().status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(responses[1].as_ref().unwrap().body(), "not found");
+        assert!(responses[2].is_none());
+    }
+
+    #[test]
+    fn add_request_with_id_rejects_invalid_ids() {
+        let mut batch = Batch::new();
+        let req = || Request::get("/").body(Bytes::new()).unwrap();
+
+        batch.add_request_with_id("2", req()).unwrap();
+
+        for content_id in ["", "a b", "<a>", "a\r\nb", "é", "2"] {
+            let err = batch.add_request_with_id(content_id, req()).unwrap_err();
+
+            assert_eq!(err.kind(), "invalid_content_id", "{:?}", content_id);
+        }
+
+        assert_eq!(batch.add_request(req()), "3");
+        assert_eq!(batch.len(), 2);
+    }
+
+    #[test]
+    fn parse_response_rejects_duplicate_content_ids() {
+        let mut batch = Batch::new();
+
+        batch.add_request(Request::get("/").body(Bytes::new()).unwrap());
+
+        let body = "--batch\r\n\
+            content-type: application/http\r\n\
+            content-id: <response-1>\r\n\
+            \r\n\
+            HTTP/1.1 204 No Content\r\n\
+            \r\n\
+            \r\n\
+            --batch\r\n\
+            content-type: application/http\r\n\
+            content-id: <1>\r\n\
+            \r\n\
+            HTTP/1.1 500 Internal Server Error\r\n\
+            \r\n\
+            \r\n\
+            --batch--\r\n";
+
+        assert_eq!(
+            batch
+                .parse_response("multipart/mixed; boundary=batch", body.into())
+                .unwrap_err()
+                .kind(),
+            "parse"
+        );
+    }
+}
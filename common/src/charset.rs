@@ -0,0 +1,139 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::error::Error;
+use std::borrow::Cow;
+
+/// The charset text fields are encoded with.
+///
+/// Only UTF-8 is available unless the `encoding` feature is enabled, which
+/// adds every encoding in the WHATWG Encoding Standard, like Shift_JIS, and
+/// windows-1252.
+///
+/// [See RFC7578 4.5](https://tools.ietf.org/html/rfc7578#section-4.5).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Charset {
+    #[cfg(feature = "encoding")]
+    encoding: &'static encoding_rs::Encoding,
+}
+
+impl Charset {
+    /// UTF-8, which is the default.
+    pub const UTF_8: Charset = Charset {
+        #[cfg(feature = "encoding")]
+        encoding: encoding_rs::UTF_8,
+    };
+
+    /// Looks up a charset by a label, like `utf-8`, `shift_jis`, or
+    /// `latin1`. Returns `None` if the charset isn't known, or isn't
+    /// enabled.
+    ///
+    /// Charsets that forms can't be encoded with, like UTF-16LE, are
+    /// replaced with the one they are encoded with instead, which is UTF-8,
+    /// so the name that is sent matches the content.
+    ///
+    /// [See the Encoding Standard](https://encoding.spec.whatwg.org/#get-an-output-encoding).
+    pub fn for_label(label: &str) -> Option<Charset> {
+        #[cfg(feature = "encoding")]
+        {
+            encoding_rs::Encoding::for_label(label.trim().as_bytes()).map(Charset::from)
+        }
+
+        #[cfg(not(feature = "encoding"))]
+        {
+            let label = label.trim();
+
+            (label.eq_ignore_ascii_case("utf-8") || label.eq_ignore_ascii_case("utf8"))
+                .then_some(Charset::UTF_8)
+        }
+    }
+
+    /// Returns the name of the charset, which is sent in the `_charset_`
+    /// field, and the charset parameter.
+    pub fn name(&self) -> &'static str {
+        #[cfg(feature = "encoding")]
+        {
+            self.encoding.name()
+        }
+
+        #[cfg(not(feature = "encoding"))]
+        {
+            "UTF-8"
+        }
+    }
+
+    /// Encodes text. Characters that can't be encoded are written as HTML
+    /// numeric character references, like a browser does.
+    pub(crate) fn encode<'t>(&self, text: &'t str) -> Cow<'t, [u8]> {
+        #[cfg(feature = "encoding")]
+        {
+            self.encoding.encode(text).0
+        }
+
+        #[cfg(not(feature = "encoding"))]
+        {
+            Cow::Borrowed(text.as_bytes())
+        }
+    }
+
+    /// Decodes text, failing on malformed input.
+    pub fn decode(&self, input: &[u8]) -> Result<String, Error> {
+        #[cfg(feature = "encoding")]
+        {
+            self.encoding
+                .decode_without_bom_handling_and_without_replacement(input)
+                .map(Cow::into_owned)
+                .ok_or(Error::Decode(self.name()))
+        }
+
+        #[cfg(not(feature = "encoding"))]
+        {
+            String::from_utf8(input.to_vec()).map_err(|_| Error::Decode(self.name()))
+        }
+    }
+}
+
+impl Default for Charset {
+    fn default() -> Charset {
+        Charset::UTF_8
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl From<&'static encoding_rs::Encoding> for Charset {
+    fn from(encoding: &'static encoding_rs::Encoding) -> Charset {
+        Charset {
+            encoding: encoding.output_encoding(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "encoding"))]
+mod tests {
+    use super::Charset;
+
+    #[test]
+    fn shift_jis_round_trip() {
+        let charset = Charset::for_label("shift_jis").unwrap();
+        let encoded = charset.encode("日本語");
+
+        assert_eq!(charset.name(), "Shift_JIS");
+        assert_eq!(&encoded[..], b"\x93\xfa\x96\x7b\x8c\xea");
+        assert_eq!(charset.decode(&encoded).unwrap(), "日本語");
+    }
+
+    #[test]
+    fn utf_16_is_replaced_with_its_output_encoding() {
+        for label in ["utf-16le", "utf-16be", "iso-2022-kr"] {
+            let charset = Charset::for_label(label).unwrap();
+
+            assert_eq!(charset, Charset::UTF_8, "{}", label);
+            assert_eq!(&charset.encode("é")[..], "é".as_bytes());
+        }
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http::header::HeaderName;
+use std::fmt::{Display, Write};
+
+/// The digest algorithms that can be computed over the content of a part.
+/// Each algorithm is enabled by the feature of the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChecksumAlgorithm {
+    /// MD5, as used by the Content-MD5 header.
+    #[cfg(feature = "md5")]
+    Md5,
+
+    /// SHA-256.
+    #[cfg(feature = "sha2")]
+    Sha256,
+
+    /// CRC-32C (Castagnoli). The digest is the checksum in big-endian order.
+    #[cfg(feature = "crc32c")]
+    Crc32c,
+}
+
+impl ChecksumAlgorithm {
+    /// Returns the name of the algorithm.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "md5")]
+            ChecksumAlgorithm::Md5 => "md5",
+            #[cfg(feature = "sha2")]
+            ChecksumAlgorithm::Sha256 => "sha256",
+            #[cfg(feature = "crc32c")]
+            ChecksumAlgorithm::Crc32c => "crc32c",
+        }
+    }
+
+    #[cfg(feature = "__checksum")]
+    fn hasher(&self) -> Hasher {
+        match *self {
+            #[cfg(feature = "md5")]
+            ChecksumAlgorithm::Md5 => Hasher::Md5(<md5::Md5 as md5::Digest>::new()),
+            #[cfg(feature = "sha2")]
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+            #[cfg(feature = "crc32c")]
+            ChecksumAlgorithm::Crc32c => Hasher::Crc32c(0),
+        }
+    }
+}
+
+/// How a digest is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DigestFormat {
+    /// Standard base64 with padding, which is used by Content-MD5, and S3.
+    #[default]
+    Base64,
+
+    /// Lowercase hexadecimal.
+    Hex,
+}
+
+impl DigestFormat {
+    fn format(&self, digest: &[u8]) -> String {
+        match *self {
+            DigestFormat::Base64 => STANDARD.encode(digest),
+            DigestFormat::Hex => digest.iter().fold(String::new(), |mut s, b| {
+                let _ = write!(s, "{:02x}", b);
+                s
+            }),
+        }
+    }
+}
+
+/// Where a checksum is sent.
+#[derive(Clone, Debug)]
+pub(crate) enum ChecksumTarget {
+    /// An HTTP trailer sent after the body.
+    Trailer(HeaderName),
+
+    /// A text part written after the part.
+    Field(String),
+}
+
+/// A digest to compute over the content of a part while the body is
+/// written, and where to send it.
+///
+/// The digest covers the content as it is written to the body, after any
+/// compression, or transfer encoding is applied.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "sha2")]
+/// # {
+/// use common_multipart_rfc7578::client::multipart::{
+///     self, Checksum, ChecksumAlgorithm, Part,
+/// };
+/// use http::header::HeaderName;
+/// use std::io::Cursor;
+///
+/// let mut form = multipart::Form::default();
+/// let checksum = Checksum::trailer(
+///     ChecksumAlgorithm::Sha256,
+///     HeaderName::from_static("x-amz-checksum-sha256"),
+/// );
+///
+/// form.add_part(Part::reader("file", Cursor::new("Hello World!")).with_checksum(checksum));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "__checksum"), allow(dead_code))]
+pub struct Checksum {
+    algorithm: ChecksumAlgorithm,
+
+    target: ChecksumTarget,
+
+    format: DigestFormat,
+}
+
+impl Checksum {
+    /// Sends the digest as an HTTP trailer with the given name. Trailers
+    /// are only sent by body implementations that support them, like the
+    /// hyper body, and are dropped by the actix body.
+    pub fn trailer(algorithm: ChecksumAlgorithm, name: HeaderName) -> Checksum {
+        Checksum {
+            algorithm,
+            target: ChecksumTarget::Trailer(name),
+            format: DigestFormat::default(),
+        }
+    }
+
+    /// Sends the digest as a text part with the given name, right after the
+    /// part it was computed over.
+    pub fn field<N>(algorithm: ChecksumAlgorithm, name: N) -> Checksum
+    where
+        N: Display,
+    {
+        Checksum {
+            algorithm,
+            target: ChecksumTarget::Field(name.to_string()),
+            format: DigestFormat::default(),
+        }
+    }
+
+    /// Sets how the digest is written. Defaults to base64.
+    pub fn with_format(mut self, format: DigestFormat) -> Checksum {
+        self.format = format;
+        self
+    }
+
+    pub(crate) fn target(&self) -> &ChecksumTarget {
+        &self.target
+    }
+}
+
+/// A digest computed over the content of a part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartChecksum {
+    name: String,
+
+    filename: Option<String>,
+
+    algorithm: ChecksumAlgorithm,
+
+    digest: Vec<u8>,
+}
+
+impl PartChecksum {
+    /// Returns the name of the part.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the filename of the part, if it has one.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the algorithm the digest was computed with.
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the digest.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Returns the digest as lowercase hexadecimal.
+    pub fn to_hex(&self) -> String {
+        DigestFormat::Hex.format(&self.digest)
+    }
+
+    /// Returns the digest as base64.
+    pub fn to_base64(&self) -> String {
+        DigestFormat::Base64.format(&self.digest)
+    }
+}
+
+/// A checksum that has been computed over a part.
+pub(crate) struct FinishedChecksum {
+    /// Where to send the formatted digest.
+    pub target: ChecksumTarget,
+
+    /// The formatted digest.
+    pub value: String,
+
+    pub checksum: PartChecksum,
+}
+
+/// The checksums being computed over the content of a part.
+pub(crate) struct PartHashers {
+    #[cfg(feature = "__checksum")]
+    hashers: Vec<(Checksum, Hasher)>,
+}
+
+impl PartHashers {
+    #[cfg_attr(not(feature = "__checksum"), allow(unused_variables))]
+    pub(crate) fn new(checksums: &[Checksum]) -> PartHashers {
+        PartHashers {
+            #[cfg(feature = "__checksum")]
+            hashers: checksums
+                .iter()
+                .map(|checksum| (checksum.clone(), checksum.algorithm.hasher()))
+                .collect(),
+        }
+    }
+
+    #[cfg_attr(not(feature = "__checksum"), allow(unused_variables))]
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        #[cfg(feature = "__checksum")]
+        for (_, hasher) in self.hashers.iter_mut() {
+            hasher.update(data);
+        }
+    }
+
+    #[cfg_attr(not(feature = "__checksum"), allow(unused_variables))]
+    pub(crate) fn finish(self, name: &str, filename: Option<&str>) -> Vec<FinishedChecksum> {
+        #[cfg(feature = "__checksum")]
+        let finished = self
+            .hashers
+            .into_iter()
+            .map(|(checksum, hasher)| {
+                let digest = hasher.finish();
+
+                FinishedChecksum {
+                    target: checksum.target,
+                    value: checksum.format.format(&digest),
+                    checksum: PartChecksum {
+                        name: name.to_string(),
+                        filename: filename.map(str::to_string),
+                        algorithm: checksum.algorithm,
+                        digest,
+                    },
+                }
+            })
+            .collect();
+        #[cfg(not(feature = "__checksum"))]
+        let finished = vec![];
+
+        finished
+    }
+}
+
+/// The running state of a digest.
+#[cfg(feature = "__checksum")]
+enum Hasher {
+    #[cfg(feature = "md5")]
+    Md5(md5::Md5),
+
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256),
+
+    #[cfg(feature = "crc32c")]
+    Crc32c(u32),
+}
+
+#[cfg(feature = "__checksum")]
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match *self {
+            #[cfg(feature = "md5")]
+            Hasher::Md5(ref mut hasher) => md5::Digest::update(hasher, data),
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(ref mut hasher) => sha2::Digest::update(hasher, data),
+            #[cfg(feature = "crc32c")]
+            Hasher::Crc32c(ref mut crc) => *crc = crc32c::crc32c_append(*crc, data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "md5")]
+            Hasher::Md5(hasher) => md5::Digest::finalize(hasher).to_vec(),
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(hasher) => sha2::Digest::finalize(hasher).to_vec(),
+            #[cfg(feature = "crc32c")]
+            Hasher::Crc32c(crc) => crc.to_be_bytes().to_vec(),
+        }
+    }
+}
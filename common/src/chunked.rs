@@ -0,0 +1,569 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Uploads of large files as a sequence of multipart requests, each with one
+//! chunk of the file.
+
+use crate::{
+    client_::{Form, Part},
+    error::Error,
+};
+use futures_util::io::{AllowStdIo, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use http::header::{HeaderValue, CONTENT_RANGE};
+use mime::Mime;
+use std::{
+    error::Error as StdError,
+    fmt::Display,
+    fs::File,
+    future::Future,
+    io::{self, Cursor, SeekFrom},
+    path::Path,
+    pin::Pin,
+};
+
+/// The size of chunks, unless it is changed.
+const DEFAULT_CHUNK_SIZE: u64 = 5 * 1024 * 1024;
+
+/// The future returned by a `Transport`.
+pub type SendFuture<'t> =
+    Pin<Box<dyn 't + Future<Output = Result<(), Box<dyn StdError + Send + Sync>>> + Send>>;
+
+/// Sends the form of each chunk of a `ChunkedUpload`, usually as a request
+/// with an HTTP client.
+///
+/// A chunk counts as uploaded once the future completes successfully. The
+/// transport should return an error if the server didn't accept it, so the
+/// upload can be resumed from it.
+pub trait Transport {
+    /// Sends the form of a chunk.
+    fn send(&mut self, chunk: Chunk, form: Form<'static>) -> SendFuture<'_>;
+}
+
+/// The position of a chunk in an upload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    index: u64,
+
+    count: u64,
+
+    offset: u64,
+
+    len: u64,
+
+    total_len: u64,
+}
+
+impl Chunk {
+    /// Returns the index of the chunk, starting from 0.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the number of chunks in the upload.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the offset of the chunk in the file.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the length of the chunk.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the chunk is empty, which is only the case for the
+    /// single chunk of an empty file.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the length of the whole file.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+
+    /// Returns true if this is the last chunk.
+    pub fn is_last(&self) -> bool {
+        self.offset + self.len >= self.total_len
+    }
+
+    /// Returns the value of a Content-Range header for the chunk, like
+    /// `bytes 0-1023/4096`. Returns `None` for the empty chunk of an empty
+    /// upload, which doesn't have a range.
+    ///
+    /// [See RFC9110 14.4](https://tools.ietf.org/html/rfc9110#section-14.4).
+    pub fn content_range(&self) -> Option<String> {
+        if self.len == 0 {
+            return None;
+        }
+
+        Some(format!(
+            "bytes {}-{}/{}",
+            self.offset,
+            self.offset + self.len - 1,
+            self.total_len
+        ))
+    }
+}
+
+/// The value of a metadata field sent with each chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChunkField {
+    /// The index of the chunk, starting from 0.
+    Index,
+
+    /// The number of chunks.
+    Count,
+
+    /// The offset of the chunk in the file.
+    Offset,
+
+    /// The length of the chunk.
+    Len,
+
+    /// The length of the whole file.
+    TotalLen,
+
+    /// The Content-Range of the chunk. The field isn't sent with an empty
+    /// chunk.
+    ContentRange,
+
+    /// The same text for every chunk, like an upload ID.
+    Text(String),
+}
+
+impl ChunkField {
+    fn value(&self, chunk: &Chunk) -> Option<String> {
+        match *self {
+            ChunkField::Index => Some(chunk.index.to_string()),
+            ChunkField::Count => Some(chunk.count.to_string()),
+            ChunkField::Offset => Some(chunk.offset.to_string()),
+            ChunkField::Len => Some(chunk.len.to_string()),
+            ChunkField::TotalLen => Some(chunk.total_len.to_string()),
+            ChunkField::ContentRange => chunk.content_range(),
+            ChunkField::Text(ref text) => Some(text.clone()),
+        }
+    }
+}
+
+/// Splits a file into fixed-size chunks, and sends each chunk with a
+/// `Transport` as a form, with the metadata fields, followed by the chunk.
+///
+/// If a chunk fails to send, the upload stops at the start of that chunk,
+/// and `upload` can be called again to resume from it. An upload can also be
+/// resumed from an offset the server reports with `resume_from`.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::{
+///     Chunk, ChunkField, ChunkedUpload, Form, SendFuture, Transport,
+/// };
+/// use futures_util::io::Cursor;
+///
+/// struct Print;
+///
+/// impl Transport for Print {
+///     fn send(&mut self, chunk: Chunk, form: Form<'static>) -> SendFuture<'_> {
+///         Box::pin(async move {
+///             println!("{:?} {}", chunk.content_range(), form.content_type());
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut upload = ChunkedUpload::new(Cursor::new(vec![0; 10_000]), 10_000)
+///     .with_chunk_size(4096)
+///     .with_filename("data.bin")
+///     .with_field("upload_id", ChunkField::Text("42".to_string()))
+///     .with_field("chunk", ChunkField::Index);
+///
+/// upload.upload(&mut Print).await.unwrap();
+///
+/// assert!(upload.is_complete());
+/// # }
+/// ```
+pub struct ChunkedUpload<R> {
+    read: R,
+
+    len: u64,
+
+    chunk_size: u64,
+
+    /// The offset of the next chunk to send.
+    offset: u64,
+
+    /// Set once the last chunk has been sent.
+    complete: bool,
+
+    /// The name of the part each chunk is sent in.
+    name: String,
+
+    filename: Option<String>,
+
+    mime: Option<Mime>,
+
+    fields: Vec<(String, ChunkField)>,
+
+    /// Whether each chunk is sent with a Content-Range header.
+    content_range: bool,
+}
+
+impl ChunkedUpload<AllowStdIo<File>> {
+    /// Creates an upload of a file, which is sent with the name of the file,
+    /// and a mime type guessed from it.
+    pub fn file<P>(path: P) -> io::Result<ChunkedUpload<AllowStdIo<File>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut upload = ChunkedUpload::new(AllowStdIo::new(file), len);
+
+        upload.filename = path
+            .file_name()
+            .map(|filename| filename.to_string_lossy().into_owned());
+        upload.mime = mime_guess::from_path(path).first();
+
+        Ok(upload)
+    }
+}
+
+impl<R> ChunkedUpload<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Creates an upload of `len` bytes of a reader, starting from the
+    /// beginning. Chunks are sent in a part named `file`.
+    pub fn new(read: R, len: u64) -> ChunkedUpload<R> {
+        ChunkedUpload {
+            read,
+            len,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            offset: 0,
+            complete: false,
+            name: "file".to_string(),
+            filename: None,
+            mime: None,
+            fields: vec![],
+            content_range: false,
+        }
+    }
+
+    /// Sets the size of each chunk. The last chunk may be smaller. The
+    /// default is 5 MiB.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> ChunkedUpload<R> {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets the name of the part each chunk is sent in.
+    pub fn with_name<N>(mut self, name: N) -> ChunkedUpload<R>
+    where
+        N: Display,
+    {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Sets the filename each chunk is sent with.
+    pub fn with_filename<F>(mut self, filename: F) -> ChunkedUpload<R>
+    where
+        F: Into<String>,
+    {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets the mime type of each chunk.
+    pub fn with_mime(mut self, mime: Mime) -> ChunkedUpload<R> {
+        self.mime = Some(mime);
+        self
+    }
+
+    /// Adds a metadata field, which is sent with each chunk, before the
+    /// chunk.
+    pub fn with_field<N>(mut self, name: N, field: ChunkField) -> ChunkedUpload<R>
+    where
+        N: Display,
+    {
+        self.fields.push((name.to_string(), field));
+        self
+    }
+
+    /// Sends each chunk with a Content-Range header in its part. The range
+    /// is also available to the transport with `Chunk::content_range`, for
+    /// APIs that expect it as a request header.
+    pub fn with_content_range(mut self) -> ChunkedUpload<R> {
+        self.content_range = true;
+        self
+    }
+
+    /// Resumes the upload from an offset, like the one reported by the
+    /// server. If the offset is in the middle of a chunk, the rest of that
+    /// chunk is sent first, with the index of the chunk, so the chunks after
+    /// it keep their index. The upload is complete if the offset is its
+    /// length.
+    pub fn resume_from(mut self, offset: u64) -> ChunkedUpload<R> {
+        self.offset = offset.min(self.len);
+        self.complete = self.len > 0 && self.offset == self.len;
+        self
+    }
+
+    /// Returns the offset of the next chunk to send, which is where the
+    /// upload resumes from.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the length of the upload.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the upload is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true once every chunk has been sent.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Sends the remaining chunks in order, stopping at the first chunk that
+    /// fails to read, or send.
+    pub async fn upload<T>(&mut self, transport: &mut T) -> Result<(), Error>
+    where
+        T: Transport,
+    {
+        if self.complete {
+            return Ok(());
+        }
+
+        self.read
+            .seek(SeekFrom::Start(self.offset))
+            .await
+            .map_err(|source| Error::ContentRead { part: None, source })?;
+
+        loop {
+            // A resumed upload may start in the middle of a chunk.
+            let len = (self.len - self.offset).min(self.chunk_size - self.offset % self.chunk_size);
+            let chunk = Chunk {
+                index: self.offset / self.chunk_size,
+                count: self.len.div_ceil(self.chunk_size).max(1),
+                offset: self.offset,
+                len,
+                total_len: self.len,
+            };
+
+            let mut content = vec![0; len as usize];
+
+            self.read
+                .read_exact(&mut content)
+                .await
+                .map_err(|source| Error::ContentRead { part: None, source })?;
+
+            let form = self.form(&chunk, content);
+
+            transport
+                .send(chunk, form)
+                .await
+                .map_err(Error::Transport)?;
+
+            self.offset += len;
+
+            if chunk.is_last() {
+                self.complete = true;
+
+                return Ok(());
+            }
+        }
+    }
+
+    /// Builds the form of a chunk.
+    fn form(&self, chunk: &Chunk, content: Vec<u8>) -> Form<'static> {
+        let mut form = Form::default();
+
+        for (name, field) in self.fields.iter() {
+            if let Some(value) = field.value(chunk) {
+                form.add_text(name, value);
+            }
+        }
+
+        let mut part = Part::reader(&self.name, Cursor::new(content));
+
+        if let Some(ref filename) = self.filename {
+            part = part.with_filename(filename.clone());
+        }
+        if let Some(ref mime) = self.mime {
+            part = part.with_mime(mime.clone());
+        }
+        if self.content_range {
+            // A range of digits is always a valid header value.
+            if let Some(Ok(value)) = chunk.content_range().map(HeaderValue::try_from) {
+                part = part.with_header(CONTENT_RANGE, value);
+            }
+        }
+        form.add_part(part);
+
+        form
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chunk, ChunkField, ChunkedUpload, SendFuture, Transport};
+    use crate::{client_::Form, error::Error, form_data::ParsedForm};
+    use futures_util::{io::Cursor, TryStreamExt};
+
+    /// Parses each form it is sent, and fails to send one chunk once.
+    #[derive(Default)]
+    struct Recorder {
+        fail_at: Option<u64>,
+        received: Vec<ParsedForm>,
+    }
+
+    impl Transport for Recorder {
+        fn send(&mut self, chunk: Chunk, form: Form<'static>) -> SendFuture<'_> {
+            Box::pin(async move {
+                if self
+                    .fail_at
+                    .take_if(|index| *index == chunk.index())
+                    .is_some()
+                {
+                    return Err("unavailable".into());
+                }
+
+                let content_type = form.content_type();
+                let body = crate::client_::Body::from(form).try_concat().await?;
+
+                self.received
+                    .push(ParsedForm::parse(&content_type, body.freeze())?);
+
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_resumes_after_failed_chunk() {
+        let content: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut transport = Recorder {
+            fail_at: Some(2),
+            ..Recorder::default()
+        };
+        let mut upload = ChunkedUpload::new(Cursor::new(content.clone()), 1000)
+            .with_chunk_size(300)
+            .with_field("index", ChunkField::Index)
+            .with_field("range", ChunkField::ContentRange)
+            .with_content_range();
+
+        assert!(matches!(
+            upload.upload(&mut transport).await,
+            Err(Error::Transport(_))
+        ));
+        assert_eq!(upload.offset(), 600);
+        assert!(!upload.is_complete());
+
+        upload.upload(&mut transport).await.unwrap();
+
+        assert!(upload.is_complete());
+        assert_eq!(transport.received.len(), 4);
+
+        let last = &transport.received[3];
+
+        assert_eq!(last.text("index").unwrap().unwrap(), "3");
+        assert_eq!(last.text("range").unwrap().unwrap(), "bytes 900-999/1000");
+
+        let uploaded: Vec<u8> = transport
+            .received
+            .iter()
+            .flat_map(|form| form.field("file").unwrap().content().to_vec())
+            .collect();
+
+        assert_eq!(uploaded, content);
+    }
+
+    #[tokio::test]
+    async fn upload_resumes_in_the_middle_of_a_chunk() {
+        let content: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut transport = Recorder::default();
+        let mut upload = ChunkedUpload::new(Cursor::new(content.clone()), 1000)
+            .with_chunk_size(300)
+            .with_field("index", ChunkField::Index)
+            .with_field("count", ChunkField::Count)
+            .with_field("range", ChunkField::ContentRange)
+            .resume_from(450);
+
+        upload.upload(&mut transport).await.unwrap();
+
+        let fields: Vec<(String, String, String)> = transport
+            .received
+            .iter()
+            .map(|form| {
+                let text = |name| form.text(name).unwrap().unwrap();
+
+                (text("index"), text("count"), text("range"))
+            })
+            .collect();
+        let uploaded: Vec<u8> = transport
+            .received
+            .iter()
+            .flat_map(|form| form.field("file").unwrap().content().to_vec())
+            .collect();
+
+        assert!(upload.is_complete());
+        assert_eq!(
+            fields,
+            [
+                ("1".into(), "4".into(), "bytes 450-599/1000".into()),
+                ("2".into(), "4".into(), "bytes 600-899/1000".into()),
+                ("3".into(), "4".into(), "bytes 900-999/1000".into()),
+            ]
+        );
+        assert_eq!(uploaded, &content[450..]);
+    }
+
+    #[tokio::test]
+    async fn upload_resumed_at_its_length_is_complete() {
+        let mut transport = Recorder::default();
+        let mut upload = ChunkedUpload::new(Cursor::new(vec![0; 900]), 900)
+            .with_chunk_size(300)
+            .resume_from(900);
+
+        assert!(upload.is_complete());
+
+        upload.upload(&mut transport).await.unwrap();
+
+        assert!(transport.received.is_empty());
+        assert_eq!(upload.offset(), 900);
+    }
+
+    #[tokio::test]
+    async fn upload_sends_one_chunk_for_empty_input() {
+        let mut transport = Recorder::default();
+        let mut upload = ChunkedUpload::new(Cursor::new(vec![]), 0)
+            .with_field("index", ChunkField::Index)
+            .with_field("range", ChunkField::ContentRange)
+            .with_content_range();
+
+        upload.upload(&mut transport).await.unwrap();
+        upload.upload(&mut transport).await.unwrap();
+
+        assert_eq!(transport.received.len(), 1);
+        assert_eq!(transport.received[0].text("index").unwrap().unwrap(), "0");
+        assert!(transport.received[0].field("range").is_none());
+    }
+}
@@ -6,40 +6,66 @@
 // copied, modified, or distributed except according to those terms.
 //
 
+#[cfg(feature = "mmap")]
+use crate::mmap::MappedFile;
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+use crate::runtime;
+#[cfg(feature = "tokio")]
+use crate::runtime::BlockingOffload;
 use crate::{
+    abort::{AbortHandle, AbortReport},
     boundary::{BoundaryGenerator, RandomAsciiGenerator},
-    error::Error,
+    charset::Charset,
+    checksum::{Checksum, ChecksumTarget, PartChecksum, PartHashers},
+    compression::Compression,
+    curl,
+    encoding::{EncodedRead, TransferEncoding},
+    error::{Error, Limit, PartContext, Timeout},
+    metrics::Metrics,
+    throttle::{RateLimiter, Sleep},
+    timeout::Timeouts,
+    trace::{BodyTrace, PartTrace},
+    urlencoded::{self, FormEncoding},
 };
-use bytes::{BufMut, BytesMut};
-use futures_core::Stream;
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_core::{ready, Stream};
 use futures_util::io::{AllowStdIo, AsyncRead, Cursor};
 use http::{
     self,
-    header::{self, HeaderName},
+    header::{self, HeaderMap, HeaderName, HeaderValue},
     request::{Builder, Request},
 };
+use memchr::memmem::Finder;
 use mime::{self, Mime};
 use std::{
     fmt::Display,
     fs::File,
     io::{self, Read},
     iter::Peekable,
-    path::Path,
+    path::{Path, PathBuf},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::{Duration, Instant},
     vec::IntoIter,
 };
 
 static CONTENT_DISPOSITION: HeaderName = header::CONTENT_DISPOSITION;
+static CONTENT_ENCODING: HeaderName = header::CONTENT_ENCODING;
 static CONTENT_TYPE: HeaderName = header::CONTENT_TYPE;
+static CONTENT_TRANSFER_ENCODING: HeaderName = HeaderName::from_static("content-transfer-encoding");
 
 /// Async streamable Multipart body.
 pub struct Body<'a> {
     /// The amount of data to write with each chunk.
     buf: BytesMut,
 
-    /// The active reader.
-    current: Option<Box<dyn 'a + AsyncRead + Send + Unpin>>,
+    /// Content that is written before any part, like a urlencoded form, or
+    /// the error that stopped it from being serialized.
+    pending: Option<Result<BytesMut, Error>>,
+
+    /// The part being written.
+    current: Option<Current<'a>>,
 
     /// The parts as an iterator. When the iterator stops
     /// yielding, the body is fully written.
@@ -47,9 +73,351 @@ pub struct Body<'a> {
 
     /// The multipart boundary.
     boundary: String,
+
+    /// Moves sync readers onto the blocking thread pool, if set.
+    #[cfg(feature = "tokio")]
+    offload: Option<BlockingOffload<'a>>,
+
+    /// The checksums computed over parts that have been written.
+    checksums: Vec<PartChecksum>,
+
+    /// Trailers to send once the body has been written.
+    trailers: HeaderMap,
+
+    /// Trailers that are computed once the body has been written.
+    computed_trailers: Vec<(HeaderName, ComputeTrailer<'a>)>,
+
+    /// The number of bytes written so far.
+    bytes_written: u64,
+
+    /// Whether the body is multipart/form-data, whose parts are written with
+    /// a Content-Disposition header.
+    form_data: bool,
+
+    /// Limits the rate chunks are written at, if set.
+    rate_limiter: Option<RateLimiter>,
+
+    /// A chunk that is waiting for the rate limiter.
+    throttled: Option<Data>,
+
+    /// The wait for the rate limiter.
+    delay: Option<Sleep>,
+
+    /// Stops the body when it is aborted, if set.
+    abort: Option<AbortHandle>,
+
+    /// The names of the parts that have been fully written.
+    sent_parts: Vec<PartContext>,
+
+    /// Set once the body has been fully written, or aborted.
+    done: bool,
+
+    /// Finds the line break, and boundary that delimit parts, which can't
+    /// appear in their content.
+    delimiter: Finder<'static>,
+
+    /// The index of the next part.
+    part_index: usize,
+
+    /// The maximum length of the content of each part, if set.
+    max_part_len: Option<u64>,
+
+    /// The maximum length of the body, if set.
+    max_body_len: Option<u64>,
+
+    /// The span of the body, if the `tracing` feature is enabled.
+    trace: BodyTrace,
+
+    /// Observes the body as it is written, if set.
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// Stops the body from waiting forever on a reader, if set.
+    timeouts: Option<Timeouts>,
+
+    /// The idle timeout of a reader that is pending.
+    idle: Option<Sleep>,
+
+    /// The total timeout, which starts when the body is first polled.
+    deadline: Option<Sleep>,
+
+    /// The length files have to be to be mapped, if they are.
+    #[cfg(feature = "mmap")]
+    map_min_len: Option<u64>,
+}
+
+/// A chunk of a body, which is either written into the body's buffer, or a
+/// slice of a mapped file.
+enum Data {
+    Buf(BytesMut),
+
+    #[cfg(feature = "mmap")]
+    Mapped(Bytes),
+}
+
+impl Data {
+    fn len(&self) -> usize {
+        match *self {
+            Data::Buf(ref buf) => buf.len(),
+            #[cfg(feature = "mmap")]
+            Data::Mapped(ref bytes) => bytes.len(),
+        }
+    }
+
+    /// Returns the chunk without copying it.
+    fn into_bytes(self) -> Bytes {
+        match self {
+            Data::Buf(buf) => buf.freeze(),
+            #[cfg(feature = "mmap")]
+            Data::Mapped(bytes) => bytes,
+        }
+    }
+
+    /// Returns the chunk, copying it if it is a slice of a mapped file.
+    fn into_bytes_mut(self) -> BytesMut {
+        match self {
+            Data::Buf(buf) => buf,
+            #[cfg(feature = "mmap")]
+            Data::Mapped(bytes) => BytesMut::from(&bytes[..]),
+        }
+    }
+}
+
+/// Computes the value of a trailer from a fully written body.
+type ComputeTrailer<'a> = Box<dyn 'a + FnOnce(&Body<'a>) -> HeaderValue + Send>;
+
+/// The part being written by a body.
+struct Current<'a> {
+    /// Where the content is written from.
+    content: Content<'a>,
+
+    /// The index of the part in the body.
+    index: usize,
+
+    /// The name of the part.
+    name: String,
+
+    /// The filename of the part.
+    filename: Option<String>,
+
+    /// The checksums being computed over the content of the part.
+    hashers: PartHashers,
+
+    /// The number of bytes of content written so far.
+    offset: u64,
+
+    /// The end of the content written so far, which may be the start of a
+    /// delimiter that continues in the next read.
+    tail: Vec<u8>,
+
+    /// The span of the part, if the `tracing` feature is enabled.
+    trace: PartTrace,
+
+    /// The time the reader has spent pending, if metrics are observed.
+    pending: Duration,
+
+    /// When the reader started pending, if it is.
+    pending_since: Option<Instant>,
+}
+
+/// Where the content of a part is written from.
+enum Content<'a> {
+    Read(Box<dyn 'a + AsyncRead + Send + Unpin>),
+
+    #[cfg(feature = "mmap")]
+    Mapped(MappedFile),
+}
+
+impl<'a> Current<'a> {
+    /// Returns where in the part an error happened.
+    fn context(&self, offset: u64) -> PartContext {
+        PartContext {
+            index: self.index,
+            name: self.name.clone(),
+            filename: self.filename.clone(),
+            offset,
+        }
+    }
+
+    /// Looks for a delimiter in content that was just read, including one
+    /// that starts in earlier content. Returns the offset of the delimiter's
+    /// `--` from the start of the read, which is negative if it starts
+    /// before the read.
+    ///
+    /// [See RFC2046 5.1.1](https://tools.ietf.org/html/rfc2046#section-5.1.1).
+    fn find_delimiter(&mut self, read: &[u8], delimiter: &Finder) -> Option<i64> {
+        // Keep enough of the end of the content for a delimiter to start in.
+        let keep = delimiter.needle().len() - 1;
+        let head = &read[..read.len().min(keep)];
+        let mut joined = std::mem::take(&mut self.tail);
+
+        joined.extend_from_slice(head);
+
+        let tail_len = joined.len() - head.len();
+        let found = match delimiter.find(&joined) {
+            Some(i) => Some(i as i64 - tail_len as i64),
+            None => delimiter.find(read).map(|i| i as i64),
+        };
+
+        self.tail = if read.len() >= keep {
+            read[read.len() - keep..].to_vec()
+        } else {
+            joined[joined.len().saturating_sub(keep)..].to_vec()
+        };
+
+        // Skip the line break, which is the end of the previous line.
+        found.map(|i| i + 2)
+    }
+
+    /// Checks content that was just read for a delimiter, and against the
+    /// part length limit, and adds it to the part.
+    fn write_content(
+        &mut self,
+        content: &[u8],
+        delimiter: &Finder,
+        max_part_len: Option<u64>,
+    ) -> Result<(), Error> {
+        if let Some(i) = self.find_delimiter(content, delimiter) {
+            let offset = (self.offset as i64 + i).max(0) as u64;
+
+            return Err(Error::BoundaryCollision {
+                part: self.context(offset),
+            });
+        }
+
+        if let Some(max) = max_part_len {
+            if self.offset + content.len() as u64 > max {
+                return Err(Error::LimitExceeded {
+                    part: Some(self.context(max)),
+                    limit: Limit::PartLen(max),
+                });
+            }
+        }
+
+        self.hashers.update(content);
+        self.offset += content.len() as u64;
+
+        Ok(())
+    }
+
+    /// Adds the time since the reader started pending, if it was.
+    fn resumed(&mut self) {
+        if let Some(since) = self.pending_since.take() {
+            self.pending += since.elapsed();
+        }
+    }
 }
 
 impl<'a> Body<'a> {
+    /// Returns a handle that aborts the body. This is the same handle as the
+    /// one returned by `Form::abort_handle`, if it was called.
+    pub fn abort_handle(&mut self) -> AbortHandle {
+        self.abort.get_or_insert_with(AbortHandle::default).clone()
+    }
+
+    /// Returns how far the body has gotten.
+    fn report(&self) -> AbortReport {
+        AbortReport {
+            sent_parts: self.sent_parts.clone(),
+            interrupted: self
+                .current
+                .as_ref()
+                .map(|current| current.context(current.offset)),
+            bytes_written: self.bytes_written,
+        }
+    }
+
+    /// Drops everything left to write, closing any open readers.
+    fn stop(&mut self) {
+        self.pending = None;
+        self.current = None;
+        self.parts = Vec::new().into_iter().peekable();
+        self.throttled = None;
+        self.delay = None;
+        self.idle = None;
+        self.deadline = None;
+        self.done = true;
+    }
+
+    /// Limits the rate the body is written at. This replaces any limiter set
+    /// on the form.
+    pub fn set_rate_limiter(&mut self, limiter: RateLimiter) {
+        self.rate_limiter = Some(limiter);
+    }
+
+    /// Observes the body as it is written. This replaces any metrics set on
+    /// the form.
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Sets the timeouts of the body. This replaces any timeouts set on the
+    /// form.
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = Some(timeouts);
+    }
+
+    /// Returns the error of the total timeout, once it has passed.
+    fn poll_deadline(&mut self, cx: &mut Context) -> Option<Error> {
+        let timeouts = self.timeouts.as_ref()?;
+        let total = timeouts.total()?;
+        let deadline = self.deadline.get_or_insert_with(|| timeouts.sleep(total));
+
+        if deadline.as_mut().poll(cx).is_pending() {
+            return None;
+        }
+
+        Some(Error::Timeout {
+            part: self
+                .current
+                .as_ref()
+                .map(|current| current.context(current.offset)),
+            timeout: Timeout::Total(total),
+        })
+    }
+
+    /// Returns the checksums that have been computed so far. Once the body
+    /// is fully written, this contains a checksum for every part that had
+    /// one.
+    pub fn checksums(&self) -> &[PartChecksum] {
+        &self.checksums
+    }
+
+    /// Returns the trailers to send after the body. These are only complete
+    /// once the body is fully written.
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+
+    /// Returns the number of bytes of the body written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns the span the body is written in. Wrappers can enter it to
+    /// emit their own events in it.
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        self.trace.span()
+    }
+
+    /// Returns the data written since the last chunk.
+    fn next_chunk(&mut self) -> BytesMut {
+        let chunk = self.buf.split();
+
+        self.bytes_written += chunk.len() as u64;
+
+        chunk
+    }
+
+    /// Computes the remaining trailers, once the body is fully written.
+    fn compute_trailers(&mut self) {
+        for (name, compute) in std::mem::take(&mut self.computed_trailers) {
+            let value = compute(self);
+
+            self.trailers.append(name, value);
+        }
+    }
+
     /// Writes a CLRF.
     fn write_crlf(&mut self) {
         self.buf.put_slice(b"\r\n");
@@ -71,53 +439,274 @@ impl<'a> Body<'a> {
         self.buf.put_slice(b"--");
     }
 
-    /// Writes the Content-Disposition, and Content-Type headers.
+    /// Writes a single header.
+    fn write_header(&mut self, name: &HeaderName, value: &[u8]) {
+        self.buf.put_slice(name.as_ref());
+        self.buf.put_slice(b": ");
+        self.buf.put_slice(value);
+        self.write_crlf();
+    }
+
+    /// Writes the Content-Type header, the Content-Disposition header of
+    /// form-data, the Content-Encoding, and Content-Transfer-Encoding headers
+    /// if the part is encoded, and the part's own headers.
     fn write_headers(&mut self, part: &Part) {
         self.write_crlf();
-        self.buf.put_slice(CONTENT_TYPE.as_ref());
-        self.buf.put_slice(b": ");
-        self.buf.put_slice(part.content_type.as_bytes());
+        self.write_header(&CONTENT_TYPE, part.content_type.as_ref().as_bytes());
+        if self.form_data {
+            self.write_header(&CONTENT_DISPOSITION, part.content_disposition().as_bytes());
+        }
+        if let Some(compression) = part.compression {
+            self.write_header(&CONTENT_ENCODING, compression.as_str().as_bytes());
+        }
+        if let Some(encoding) = part.transfer_encoding {
+            self.write_header(&CONTENT_TRANSFER_ENCODING, encoding.as_str().as_bytes());
+        }
+        for (name, value) in part.headers.iter() {
+            self.write_header(name, value.as_bytes());
+        }
         self.write_crlf();
-        self.buf.put_slice(CONTENT_DISPOSITION.as_ref());
-        self.buf.put_slice(b": ");
-        self.buf.put_slice(part.content_disposition.as_bytes());
+    }
+
+    /// Writes a text part, without the boundary that follows it. The name is
+    /// only written in a form-data body.
+    fn write_text_part(&mut self, name: &str, text: &str) {
+        self.write_boundary();
+        self.write_crlf();
+        self.write_header(&CONTENT_TYPE, mime::TEXT_PLAIN.as_ref().as_bytes());
+        if self.form_data {
+            self.write_header(
+                &CONTENT_DISPOSITION,
+                format!("form-data; name=\"{}\"", name).as_bytes(),
+            );
+        }
+        self.write_crlf();
+        self.buf.put_slice(text.as_bytes());
         self.write_crlf();
+    }
+
+    /// Opens the content of a part, mapping it if it is a file that should
+    /// be mapped.
+    fn open_content(&self, part: Part<'a>) -> Result<Content<'a>, Error> {
+        #[cfg(feature = "mmap")]
+        if let (Some(min_len), Some(path)) = (self.map_min_len, part.mappable_path()) {
+            // SAFETY: `Form::map_files` requires files not to be truncated
+            // while they are being written.
+            if let Some(mapped) = unsafe { MappedFile::open(path, min_len)? } {
+                return Ok(Content::Mapped(mapped));
+            }
+        }
+
+        #[cfg(feature = "tokio")]
+        let read = part.into_reader(self.offload);
+        #[cfg(not(feature = "tokio"))]
+        let read = part.into_reader();
+
+        read.map(Content::Read)
+    }
+
+    /// Writes the end of the part being written, and the final boundary if
+    /// it is the last part.
+    fn finish_part(&mut self) {
         self.write_crlf();
+        if let Some(current) = self.current.take() {
+            current.trace.finished(current.offset);
+            if let Some(ref metrics) = self.metrics {
+                metrics.part_written(&current.name, current.offset);
+                metrics.reader_pending(&current.name, current.pending);
+            }
+            self.sent_parts.push(current.context(current.offset));
+            self.finish_checksums(current);
+        }
+        if self.parts.peek().is_none() {
+            // If there is no next part, write the final boundary
+            self.write_final_boundary();
+            self.write_crlf();
+        }
+    }
+
+    /// Finishes the checksums of a part that has been written, and sends
+    /// them where they were configured to go.
+    fn finish_checksums(&mut self, current: Current<'a>) {
+        let finished = current
+            .hashers
+            .finish(&current.name, current.filename.as_deref());
+
+        for finished in finished {
+            match finished.target {
+                ChecksumTarget::Trailer(name) => {
+                    // A formatted digest is always a valid header value.
+                    if let Ok(value) = HeaderValue::from_str(&finished.value) {
+                        self.trailers.append(name, value);
+                    }
+                }
+                ChecksumTarget::Field(name) => self.write_text_part(&name, &finished.value),
+            }
+
+            self.checksums.push(finished.checksum);
+        }
     }
 }
 
-impl<'a> Stream for Body<'a> {
-    type Item = Result<BytesMut, Error>;
+impl<'a> Body<'a> {
+    /// Writes the next chunk, failing if it goes over the body length limit.
+    ///
+    /// Every error stops the body, so a consumer that keeps polling after
+    /// one can't send a body that looks complete, but is missing content.
+    fn poll_chunk(&mut self, cx: &mut Context) -> Poll<Option<Result<Data, Error>>> {
+        let mut chunk = ready!(self.poll_write(cx));
+
+        if let (Some(Ok(_)), Some(max)) = (&chunk, self.max_body_len) {
+            if self.bytes_written > max {
+                let part = self
+                    .current
+                    .as_ref()
+                    .map(|current| current.context(current.offset));
+
+                chunk = Some(Err(Error::LimitExceeded {
+                    part,
+                    limit: Limit::BodyLen(max),
+                }));
+            }
+        }
+
+        if let Some(Err(_)) = chunk {
+            self.stop();
+        }
+
+        Poll::Ready(chunk)
+    }
 
     /// Iterate over each form part, and write it out.
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        let body = self.get_mut();
+    fn poll_write(&mut self, cx: &mut Context) -> Poll<Option<Result<Data, Error>>> {
+        let body = self;
+
+        if let Some(pending) = body.pending.take() {
+            if let Ok(ref chunk) = pending {
+                body.bytes_written += chunk.len() as u64;
+            }
+
+            cx.waker().wake_by_ref();
+
+            return Poll::Ready(Some(pending.map(Data::Buf)));
+        }
 
         match body.current {
             None => {
                 if let Some(part) = body.parts.next() {
+                    let index = body.part_index;
+                    let context = PartContext {
+                        index,
+                        name: part.name.clone(),
+                        filename: part.filename.clone(),
+                        offset: 0,
+                    };
+
+                    body.part_index += 1;
+
+                    // A line break would end the header, and let the name
+                    // write headers of its own. This goes for the names of
+                    // the checksum fields that follow the part as well.
+                    if body.form_data {
+                        let invalid = |value: &str| value.contains(['\r', '\n', '\0']);
+                        let invalid_field = |checksum: &Checksum| matches!(checksum.target(), ChecksumTarget::Field(name) if invalid(name));
+
+                        if invalid(&part.name)
+                            || part.filename.as_deref().is_some_and(invalid)
+                            || part.checksums.iter().any(invalid_field)
+                        {
+                            return Poll::Ready(Some(Err(Error::InvalidHeader {
+                                part: context,
+                                header: CONTENT_DISPOSITION.to_string(),
+                            })));
+                        }
+                    }
+
                     body.write_boundary();
                     body.write_headers(&part);
 
-                    let read: Box<dyn AsyncRead + Send + Unpin> = match part.inner {
-                        Inner::Read(read) => Box::new(AllowStdIo::new(read)),
-                        Inner::AsyncRead(read) => read,
-                        Inner::Text(s) => Box::new(Cursor::new(s)),
+                    let trace = body.trace.part(index, &part);
+                    let hashers = PartHashers::new(&part.checksums);
+
+                    let content = match body.open_content(part) {
+                        Ok(content) => content,
+                        Err(e) => {
+                            // Nothing from this part has been sent yet.
+                            body.buf.clear();
+                            return Poll::Ready(Some(Err(e.in_part(context))));
+                        }
                     };
 
-                    body.current = Some(read);
+                    body.current = Some(Current {
+                        content,
+                        index,
+                        name: context.name,
+                        filename: context.filename,
+                        hashers,
+                        offset: 0,
+                        // The content follows a line break, which is part of
+                        // a delimiter that starts the content.
+                        tail: b"\r\n".to_vec(),
+                        trace,
+                        pending: Duration::ZERO,
+                        pending_since: None,
+                    });
 
                     cx.waker().wake_by_ref();
 
-                    Poll::Ready(Some(Ok(body.buf.split())))
+                    Poll::Ready(Some(Ok(Data::Buf(body.next_chunk()))))
                 } else {
                     // No current part, and no parts left means there is nothing
                     // left to write.
                     //
+                    body.compute_trailers();
+                    body.done = true;
+                    body.trace.finished(body.bytes_written);
+
                     Poll::Ready(None)
                 }
             }
-            Some(ref mut read) => {
+            Some(ref mut current) => {
+                // Without mapped files, every part is read.
+                #[cfg_attr(not(feature = "mmap"), allow(clippy::infallible_destructuring_match))]
+                let read = match current.content {
+                    Content::Read(ref mut read) => read,
+                    #[cfg(feature = "mmap")]
+                    Content::Mapped(ref mut mapped) => {
+                        let chunk = match mapped.next_chunk() {
+                            Ok(chunk) => chunk,
+                            Err(e) => {
+                                let part = current.context(current.offset);
+
+                                return Poll::Ready(Some(Err(e.in_part(part))));
+                            }
+                        };
+
+                        return match chunk {
+                            Some(chunk) => {
+                                let written = current.write_content(
+                                    &chunk,
+                                    &body.delimiter,
+                                    body.max_part_len,
+                                );
+
+                                if let Err(e) = written {
+                                    return Poll::Ready(Some(Err(e)));
+                                }
+
+                                body.bytes_written += chunk.len() as u64;
+
+                                Poll::Ready(Some(Ok(Data::Mapped(chunk))))
+                            }
+                            None => {
+                                body.finish_part();
+
+                                Poll::Ready(Some(Ok(Data::Buf(body.next_chunk()))))
+                            }
+                        };
+                    }
+                };
+
                 // Reserve some space to read the next part
                 body.buf.reserve(256);
                 let len_before = body.buf.len();
@@ -129,29 +718,75 @@ impl<'a> Stream for Body<'a> {
                 match Pin::new(read).poll_read(cx, slice) {
                     Poll::Pending => {
                         body.buf.truncate(len_before);
+                        current.trace.pending();
+                        if body.metrics.is_some() {
+                            current.pending_since.get_or_insert_with(Instant::now);
+                        }
+
+                        let idle = match body.timeouts {
+                            Some(ref timeouts) => timeouts.idle().map(|idle| (timeouts, idle)),
+                            None => None,
+                        };
+
+                        if let Some((timeouts, idle)) = idle {
+                            let sleep = body.idle.get_or_insert_with(|| timeouts.sleep(idle));
+
+                            if sleep.as_mut().poll(cx).is_ready() {
+                                let err = Error::Timeout {
+                                    part: Some(current.context(current.offset)),
+                                    timeout: Timeout::Idle(idle),
+                                };
+
+                                body.stop();
+                                return Poll::Ready(Some(Err(err)));
+                            }
+                        }
+
                         Poll::Pending
                     }
                     // Read some data.
                     Poll::Ready(Ok(bytes_read)) => {
                         body.buf.truncate(len_before + bytes_read);
+                        current.trace.ready();
+                        current.resumed();
+                        body.idle = None;
+
+                        let written = current.write_content(
+                            &body.buf[len_before..],
+                            &body.delimiter,
+                            body.max_part_len,
+                        );
+
+                        if let Err(e) = written {
+                            body.buf.truncate(len_before);
+                            return Poll::Ready(Some(Err(e)));
+                        }
 
                         if bytes_read == 0 {
                             // EOF: No data left to read. Get ready to move onto write the next part.
-                            body.current = None;
-                            body.write_crlf();
-                            if body.parts.peek().is_none() {
-                                // If there is no next part, write the final boundary
-                                body.write_final_boundary();
-                                body.write_crlf();
-                            }
+                            body.finish_part();
                         }
 
-                        Poll::Ready(Some(Ok(body.buf.split())))
+                        Poll::Ready(Some(Ok(Data::Buf(body.next_chunk()))))
                     }
                     // Error reading from underlying stream.
-                    Poll::Ready(Err(e)) => {
+                    Poll::Ready(Err(source)) => {
                         body.buf.truncate(len_before);
-                        Poll::Ready(Some(Err(Error::ContentRead(e))))
+                        current.trace.ready();
+                        current.resumed();
+                        body.idle = None;
+
+                        if let Some(ref metrics) = body.metrics {
+                            metrics.reader_pending(&current.name, current.pending);
+                        }
+
+                        let err = Error::ContentRead {
+                            part: Some(current.context(current.offset)),
+                            source,
+                        };
+
+                        current.trace.failed(current.offset, &err);
+                        Poll::Ready(Some(Err(err)))
                     }
                 }
             }
@@ -159,6 +794,123 @@ impl<'a> Stream for Body<'a> {
     }
 }
 
+impl<'a> Drop for Body<'a> {
+    /// Records how far a body got if it is dropped before it is written.
+    fn drop(&mut self) {
+        if let (Some(handle), false) = (&self.abort, self.done) {
+            handle.set_report(self.report());
+        }
+    }
+}
+
+impl<'a> Stream for Body<'a> {
+    type Item = Result<BytesMut, Error>;
+
+    /// Writes the next chunk. A slice of a mapped file is copied, which
+    /// `Body::poll_bytes` avoids.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_data(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Data::into_bytes_mut)))
+    }
+}
+
+impl<'a> Body<'a> {
+    /// Writes the next chunk like `Stream::poll_next`, but as `Bytes`, so
+    /// slices of mapped files are returned without being copied.
+    pub fn poll_bytes(&mut self, cx: &mut Context) -> Poll<Option<Result<Bytes, Error>>> {
+        self.poll_data(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Data::into_bytes)))
+    }
+
+    /// Writes the next chunk in the body's span.
+    fn poll_data(&mut self, cx: &mut Context) -> Poll<Option<Result<Data, Error>>> {
+        let body = self;
+
+        #[cfg(feature = "tracing")]
+        let span = body.trace.span().clone();
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        body.trace.polled();
+
+        let chunk = ready!(body.poll_throttled(cx));
+
+        match chunk {
+            Some(Ok(ref chunk)) => {
+                if let Some(ref metrics) = body.metrics {
+                    metrics.bytes_written(chunk.len() as u64);
+                }
+            }
+            Some(Err(ref err)) => {
+                body.trace.failed(err);
+                if let Some(ref metrics) = body.metrics {
+                    metrics.write_error(err);
+                }
+            }
+            None => (),
+        }
+
+        Poll::Ready(chunk)
+    }
+
+    /// Writes the next chunk, once the rate limiter allows it.
+    fn poll_throttled(&mut self, cx: &mut Context) -> Poll<Option<Result<Data, Error>>> {
+        let body = self;
+
+        if body.done {
+            return Poll::Ready(None);
+        }
+
+        if let Some(ref handle) = body.abort {
+            if handle.is_aborted() {
+                let report = body.report();
+
+                handle.set_report(report.clone());
+                body.stop();
+
+                return Poll::Ready(Some(Err(Error::Aborted(report))));
+            }
+
+            handle.register(cx.waker());
+        }
+
+        if let Some(err) = body.poll_deadline(cx) {
+            body.stop();
+
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        let limiter = match body.rate_limiter {
+            Some(ref limiter) => limiter.clone(),
+            None => return body.poll_chunk(cx),
+        };
+
+        loop {
+            if let Some(ref mut delay) = body.delay {
+                ready!(delay.as_mut().poll(cx));
+                body.delay = None;
+            }
+
+            let chunk = match body.throttled.take() {
+                Some(chunk) => chunk,
+                None => match ready!(body.poll_chunk(cx)) {
+                    Some(Ok(chunk)) => chunk,
+                    other => return Poll::Ready(other),
+                },
+            };
+
+            match limiter.acquire(chunk.len()) {
+                None => return Poll::Ready(Some(Ok(chunk))),
+                Some(wait) => {
+                    body.throttled = Some(chunk);
+                    body.delay = Some(limiter.sleep(wait));
+                }
+            }
+        }
+    }
+}
+
 /// Implements the multipart/form-data media type as described by
 /// RFC 7578.
 ///
@@ -170,6 +922,46 @@ pub struct Form<'a> {
     ///
     /// [See](https://tools.ietf.org/html/rfc7578#section-4.1).
     boundary: String,
+
+    /// The multipart subtype, which is form-data unless it is changed.
+    subtype: String,
+
+    /// The charset text parts are encoded with, if it is declared.
+    charset: Option<Charset>,
+
+    /// How the form is serialized.
+    encoding: FormEncoding,
+
+    /// Limits the rate the body is written at, if set.
+    rate_limiter: Option<RateLimiter>,
+
+    /// Aborts the body, if set.
+    abort: Option<AbortHandle>,
+
+    /// Observes the body as it is written, if set.
+    metrics: Option<Arc<dyn Metrics>>,
+
+    /// Stops the body from waiting forever on a reader, if set.
+    timeouts: Option<Timeouts>,
+
+    /// The length files have to be to be mapped, if they are.
+    #[cfg(feature = "mmap")]
+    map_min_len: Option<u64>,
+
+    /// The maximum length of the content of each part, if set.
+    max_part_len: Option<u64>,
+
+    /// The maximum length of the body, if set.
+    max_body_len: Option<u64>,
+
+    #[cfg(feature = "tokio")]
+    offload: Option<BlockingOffload<'a>>,
+
+    /// Trailers with a value known up front.
+    trailers: HeaderMap,
+
+    /// Trailers that are computed once the body has been written.
+    computed_trailers: Vec<(HeaderName, ComputeTrailer<'a>)>,
 }
 
 impl<'a> Default for Form<'a> {
@@ -209,6 +1001,21 @@ impl<'a> Form<'a> {
         Form {
             parts: vec![],
             boundary: G::generate_boundary(),
+            subtype: "form-data".to_string(),
+            charset: None,
+            encoding: FormEncoding::Multipart,
+            rate_limiter: None,
+            abort: None,
+            metrics: None,
+            timeouts: None,
+            #[cfg(feature = "mmap")]
+            map_min_len: None,
+            max_part_len: None,
+            max_body_len: None,
+            #[cfg(feature = "tokio")]
+            offload: None,
+            trailers: HeaderMap::new(),
+            computed_trailers: vec![],
         }
     }
 
@@ -237,22 +1044,112 @@ impl<'a> Form<'a> {
         ))
     }
 
-    /// Adds a readable part to the Form.
+    /// Adds a part to the Form.
     ///
     /// # Examples
     ///
     /// ```
-    /// use common_multipart_rfc7578::client::multipart;
-    /// use std::io::Cursor;
+    /// use common_multipart_rfc7578::client::multipart::{self, Part};
     ///
-    /// let bytes = Cursor::new("Hello World!");
     /// let mut form = multipart::Form::default();
     ///
-    /// form.add_reader("input", bytes);
+    /// form.add_part(Part::text("text", "Hello World!"));
     /// ```
-    pub fn add_reader<F, R>(&mut self, name: F, read: R)
-    where
-        F: Display,
+    pub fn add_part(&mut self, part: Part<'a>) {
+        self.parts.push(part);
+    }
+
+    /// Inserts a part at a position in the Form, shifting the parts after
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart::{self, Part};
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_text("second", "World");
+    /// form.insert(0, Part::text("first", "Hello"));
+    ///
+    /// assert_eq!(form.parts().next().unwrap().name(), "first");
+    /// ```
+    pub fn insert(&mut self, index: usize, part: Part<'a>) {
+        self.parts.insert(index, part);
+    }
+
+    /// Removes every part with the given name, and returns them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_text("token", "secret");
+    /// form.add_text("text", "Hello World!");
+    ///
+    /// assert_eq!(form.remove("token").len(), 1);
+    /// assert!(!form.contains("token"));
+    /// ```
+    pub fn remove(&mut self, name: &str) -> Vec<Part<'a>> {
+        let (removed, kept) = std::mem::take(&mut self.parts)
+            .into_iter()
+            .partition(|part| part.name == name);
+
+        self.parts = kept;
+        removed
+    }
+
+    /// Keeps only the parts the predicate returns true for.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Part<'a>) -> bool,
+    {
+        self.parts.retain(f);
+    }
+
+    /// Returns true if the Form has a part with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.parts.iter().any(|part| part.name == name)
+    }
+
+    /// Returns the parts, in the order they are written.
+    pub fn parts(&self) -> impl Iterator<Item = &Part<'a>> {
+        self.parts.iter()
+    }
+
+    /// Returns the number of parts.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Returns true if the Form doesn't have any parts.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Adds a readable part to the Form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    /// use std::io::Cursor;
+    ///
+    /// let bytes = Cursor::new("Hello World!");
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_reader("input", bytes);
+    /// ```
+    pub fn add_reader<F, R>(&mut self, name: F, read: R)
+    where
+        F: Display,
         R: 'a + Read + Send + Unpin,
     {
         let read = Box::new(read);
@@ -291,6 +1188,10 @@ impl<'a> Form<'a> {
 
     /// Adds a file, and attempts to derive the mime type.
     ///
+    /// The file is opened when the body reaches its part, and closed once the
+    /// part is written. If it can't be opened then, the body returns
+    /// [`Error::FileOpen`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -306,7 +1207,7 @@ impl<'a> Form<'a> {
         P: AsRef<Path>,
         F: Display,
     {
-        self._add_file(name, path, None)
+        self._add_file(name, path, None, Inner::File)
     }
 
     /// Adds a file with the specified mime type to the form.
@@ -327,41 +1228,114 @@ impl<'a> Form<'a> {
         P: AsRef<Path>,
         F: Display,
     {
-        self._add_file(name, path, Some(mime))
+        self._add_file(name, path, Some(mime), Inner::File)
     }
 
-    /// Internal method for adding a file part to the form.
-    fn _add_file<P, F>(&mut self, name: F, path: P, mime: Option<Mime>) -> io::Result<()>
+    /// Adds a file that is read without blocking the async runtime, and
+    /// attempts to derive the mime type.
+    ///
+    /// Reads are performed on the runtime's blocking thread pool. With the
+    /// `tokio` feature, the body must be polled from within a tokio runtime.
+    /// With only the `async-std` feature, async-std's thread pool is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_file_async("file", format!("../{}", file!()))
+    ///     .expect("file to exist");
+    /// ```
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    pub fn add_file_async<P, F>(&mut self, name: F, path: P) -> io::Result<()>
     where
         P: AsRef<Path>,
         F: Display,
     {
-        let f = File::open(&path)?;
-        let mime = mime.or_else(|| mime_guess::from_path(&path).first());
+        self._add_file(name, path, None, Inner::AsyncFile)
+    }
 
-        // Early return if the file metadata could not be accessed. This MIGHT
-        // not be an error, if the file could be opened.
-        let meta = f.metadata()?;
+    /// Adds a file with the specified mime type that is read without
+    /// blocking the async runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_file_async_with_mime("data", "test.csv", mime::TEXT_CSV);
+    /// ```
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    pub fn add_file_async_with_mime<P, F>(&mut self, name: F, path: P, mime: Mime) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        F: Display,
+    {
+        self._add_file(name, path, Some(mime), Inner::AsyncFile)
+    }
 
-        if !meta.is_file() {
-            // If the path is not a file, it can't be uploaded because there
-            // is no content.
+    /// Internal method for adding a file part to the form.
+    fn _add_file<P, F>(
+        &mut self,
+        name: F,
+        path: P,
+        mime: Option<Mime>,
+        inner: fn(PathBuf) -> Inner<'a>,
+    ) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        F: Display,
+    {
+        self.parts.push(Part::from_path(name, path, mime, inner)?);
 
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "expected a file not directory",
+        Ok(())
+    }
+
+    /// Adds every file in a directory to the form, each as a part with the
+    /// same name. Requires the `directory` feature.
+    ///
+    /// The filename of each part is its path relative to the parent of the
+    /// directory, separated by `/`, which is how browsers send a directory
+    /// selected with `webkitdirectory`. Files are only opened once the body
+    /// reaches their part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart::{self, DirectoryOptions};
+    ///
+    /// let mut form = multipart::Form::default();
+    /// let options = DirectoryOptions::new().include("*.rs");
+    ///
+    /// form.add_directory("files", "src", options)
+    ///     .expect("directory to exist");
+    /// ```
+    #[cfg(feature = "directory")]
+    pub fn add_directory<P, F>(
+        &mut self,
+        name: F,
+        path: P,
+        options: crate::directory::DirectoryOptions,
+    ) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        F: Display,
+    {
+        for entry in crate::directory::walk(path.as_ref(), &options)? {
+            let mime = mime_guess::from_path(&entry.path).first();
+
+            self.parts.push(Part::new(
+                Inner::File(entry.path),
+                &name,
+                mime,
+                Some(entry.filename),
             ));
         }
 
-        let read = Box::new(f);
-
-        self.parts.push(Part::new(
-            Inner::Read(read),
-            name,
-            mime,
-            Some(path.as_ref().as_os_str().to_string_lossy()),
-        ));
-
         Ok(())
     }
 
@@ -487,7 +1461,9 @@ impl<'a> Form<'a> {
     }
 
     /// Updates a request instance with the multipart Content-Type header
-    /// and the payload data.
+    /// and the payload data. The form is sent urlencoded instead if it is
+    /// set with `Form::set_encoding`, which fails if it has a part that
+    /// can't be urlencoded.
     ///
     /// # Examples
     ///
@@ -501,7 +1477,7 @@ impl<'a> Form<'a> {
     /// form.add_text("text", "Hello World!");
     /// let req = form.set_body::<multipart::Body>(req_builder).unwrap();
     /// ```
-    pub fn set_body<B>(self, req: Builder) -> Result<Request<B>, http::Error>
+    pub fn set_body<B>(self, req: Builder) -> Result<Request<B>, Error>
     where
         B: From<Body<'a>>,
     {
@@ -530,259 +1506,2157 @@ impl<'a> Form<'a> {
     /// ```
     // Dev note: I am not sure this function is useful anymore, I could not fix the test
     // with something besides an identity transform.
-    pub fn set_body_convert<B, I>(self, req: Builder) -> Result<Request<B>, http::Error>
+    pub fn set_body_convert<B, I>(self, req: Builder) -> Result<Request<B>, Error>
     where
         I: From<Body<'a>> + Into<B>,
     {
-        req.header(&CONTENT_TYPE, self.content_type().as_str())
-            .body(I::from(Body::from(self)).into())
-    }
+        if self.is_urlencoded() {
+            if let Some(part) = self
+                .parts
+                .iter()
+                .find(|part| part.urlencoded_text().is_none())
+            {
+                return Err(Error::NotUrlEncodable {
+                    name: part.name().to_string(),
+                });
+            }
+        }
 
-    pub fn content_type(&self) -> String {
-        format!("multipart/form-data; boundary={}", &self.boundary)
-    }
-}
+        let mut req = req.header(&CONTENT_TYPE, self.content_type().as_str());
 
-impl<'a> From<Form<'a>> for Body<'a> {
-    /// Turns a `Form` into a multipart `Body`.
-    fn from(form: Form<'a>) -> Self {
-        Body {
-            buf: BytesMut::with_capacity(2048),
-            current: None,
-            parts: form.parts.into_iter().peekable(),
-            boundary: form.boundary,
+        // HTTP/1.1 trailers have to be declared in the request headers.
+        //
+        // [See](https://tools.ietf.org/html/rfc9110#section-6.6.2).
+        let trailer_names = self.trailer_names();
+
+        if !trailer_names.is_empty() {
+            req = req.header(header::TRAILER, trailer_names.join(", "));
         }
-    }
-}
 
-/// One part of a body delimited by a boundary line.
-///
-/// [See RFC2046 5.1](https://tools.ietf.org/html/rfc2046#section-5.1).
-pub struct Part<'a> {
-    inner: Inner<'a>,
+        Ok(req.body(I::from(Body::from(self)).into())?)
+    }
 
-    /// Each part can include a Content-Type header field. If this
-    /// is not specified, it defaults to "text/plain", or
-    /// "application/octet-stream" for file data.
+    /// Adds a trailer to send after the body. Trailers are only sent by body
+    /// implementations that support them, like the hyper body, and require
+    /// HTTP/2, or a chunked HTTP/1.1 request.
     ///
-    /// [See](https://tools.ietf.org/html/rfc7578#section-4.4)
-    content_type: String,
-
-    /// Each part must contain a Content-Disposition header field.
+    /// # Examples
     ///
-    /// [See](https://tools.ietf.org/html/rfc7578#section-4.2).
-    content_disposition: String,
-}
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    /// use http::header::{HeaderName, HeaderValue};
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_trailer(
+    ///     HeaderName::from_static("x-upload-source"),
+    ///     HeaderValue::from_static("nightly"),
+    /// );
+    /// ```
+    pub fn add_trailer(&mut self, name: HeaderName, value: HeaderValue) {
+        self.trailers.append(name, value);
+    }
 
-impl<'a> Part<'a> {
-    /// Internal method to build a new Part instance. Sets the disposition type,
-    /// content-type, and the disposition parameters for name, and optionally
-    /// for filename.
+    /// Adds a trailer with a value computed once the body has been written,
+    /// for example from the number of bytes written, or the checksums of the
+    /// parts.
     ///
-    /// Per [4.3](https://tools.ietf.org/html/rfc7578#section-4.3), if multiple
-    /// files need to be specified for one form field, they can all be specified
-    /// with the same name parameter.
-    fn new<N, F>(inner: Inner<'a>, name: N, mime: Option<Mime>, filename: Option<F>) -> Part<'a>
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    /// use http::header::{HeaderName, HeaderValue};
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_text("text", "Hello World!");
+    /// form.add_computed_trailer(HeaderName::from_static("x-upload-length"), |body| {
+    ///     HeaderValue::from(body.bytes_written())
+    /// });
+    /// ```
+    pub fn add_computed_trailer<F>(&mut self, name: HeaderName, compute: F)
     where
-        N: Display,
-        F: Display,
+        F: 'a + FnOnce(&Body<'a>) -> HeaderValue + Send,
     {
-        // `name` disposition parameter is required. It should correspond to the
-        // name of a form field.
-        //
-        // [See 4.2](https://tools.ietf.org/html/rfc7578#section-4.2)
-        //
-        let mut disposition_params = vec![format!("name=\"{}\"", name)];
+        self.computed_trailers.push((name, Box::new(compute)));
+    }
 
-        // `filename` can be supplied for files, but is totally optional.
-        //
-        // [See 4.2](https://tools.ietf.org/html/rfc7578#section-4.2)
-        //
-        if let Some(filename) = filename {
-            disposition_params.push(format!("filename=\"{}\"", filename));
+    /// Returns the names of the trailers the body will send.
+    fn trailer_names(&self) -> Vec<&str> {
+        let checksums = self
+            .parts
+            .iter()
+            .flat_map(|part| part.checksums.iter())
+            .filter_map(|checksum| match checksum.target() {
+                ChecksumTarget::Trailer(name) => Some(name),
+                ChecksumTarget::Field(_) => None,
+            });
+        let computed = self.computed_trailers.iter().map(|(name, _)| name);
+
+        let mut names: Vec<&str> = vec![];
+
+        for name in self.trailers.keys().chain(checksums).chain(computed) {
+            if !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
         }
 
-        let content_type = format!("{}", mime.unwrap_or_else(|| inner.default_content_type()));
+        names
+    }
 
-        Part {
-            inner,
-            content_type,
-            content_disposition: format!("form-data; {}", disposition_params.join("; ")),
+    pub fn content_type(&self) -> String {
+        if self.is_urlencoded() {
+            urlencoded::CONTENT_TYPE.to_string()
+        } else {
+            format!("multipart/{}; boundary={}", &self.subtype, &self.boundary)
         }
     }
-}
 
-enum Inner<'a> {
-    /// The `Read` and `AsyncRead` variants captures multiple cases.
+    /// Limits the rate the body of the form is written at. A limiter can be
+    /// shared by several forms to cap their total rate.
     ///
-    ///   * The first is it supports uploading a file, which is explicitly
-    ///     described in RFC 7578.
+    /// # Examples
     ///
-    ///   * The second (which is not described by RFC 7578), is it can handle
-    ///     arbitrary input streams (for example, a server response).
-    ///     Any arbitrary input stream is automatically considered a file,
-    ///     and assigned the corresponding content type if not explicitly
-    ///     specified.
-    Read(Box<dyn 'a + Read + Send + Unpin>),
-
-    AsyncRead(Box<dyn 'a + AsyncRead + Send + Unpin>),
-
-    /// The `String` variant handles "text/plain" form data payloads.
-    Text(String),
-}
-
-impl<'a> Inner<'a> {
-    /// Returns the default Content-Type header value as described in section 4.4.
+    /// ```
+    /// # #[cfg(feature = "async-std")]
+    /// # {
+    /// use common_multipart_rfc7578::client::multipart::{self, AsyncStdTimer, RateLimiter};
     ///
-    /// [See](https://tools.ietf.org/html/rfc7578#section-4.4)
-    fn default_content_type(&self) -> Mime {
-        match *self {
-            Inner::Read(_) | Inner::AsyncRead(_) => mime::APPLICATION_OCTET_STREAM,
-            Inner::Text(_) => mime::TEXT_PLAIN,
-        }
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.set_rate_limiter(RateLimiter::new(1024, 1024, AsyncStdTimer));
+    /// # }
+    /// ```
+    pub fn set_rate_limiter(&mut self, limiter: RateLimiter) {
+        self.rate_limiter = Some(limiter);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{Body, Form};
-    use crate::error::Error;
-    use bytes::BytesMut;
-    use futures_util::TryStreamExt;
-    use std::{
-        io::Cursor,
-        path::{Path, PathBuf},
-    };
-
-    async fn form_output(form: Form<'_>) -> String {
-        let result: Result<BytesMut, Error> = Body::from(form).try_concat().await;
-
-        assert!(result.is_ok());
-
-        let bytes = result.unwrap();
-        let data = std::str::from_utf8(bytes.as_ref()).unwrap();
 
-        data.into()
+    /// Returns a handle that aborts the body of the form once it is being
+    /// written. See [`AbortHandle`].
+    pub fn abort_handle(&mut self) -> AbortHandle {
+        self.abort.get_or_insert_with(AbortHandle::default).clone()
     }
 
-    fn test_file_path() -> PathBuf {
-        // common/src/data/test.txt
-        Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("src")
-            .join("data")
-            .join("test.txt")
+    /// Observes the body of the form as it is written, to export metrics.
+    /// See [`Metrics`].
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.metrics = Some(metrics);
     }
 
-    #[tokio::test]
-    async fn add_text_returns_expected_result() {
-        let mut form = Form::default();
+    /// Sets timeouts that stop the body of the form from waiting forever on
+    /// a reader. See [`Timeouts`].
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = Some(timeouts);
+    }
 
-        form.add_text("test", "Hello World!");
+    /// Writes files that are at least `min_len` bytes long from a memory
+    /// mapping, rather than reading them. This avoids a read, and a copy for
+    /// every chunk of a large file when the body is written with
+    /// `Body::poll_bytes`, like the hyper, and actix bodies are.
+    ///
+    /// Files that are compressed, or transfer encoded are still read.
+    ///
+    /// The length of a mapped file is checked before each chunk, and the
+    /// body fails with `Error::FileChanged` if it changed.
+    ///
+    /// # Safety
+    ///
+    /// A mapped file must not be truncated while the body, or any chunk it
+    /// returned is alive. Reading a mapping past the end of its file raises
+    /// `SIGBUS` on Unix, and a file truncated between two checks, or after
+    /// its last chunk was returned, can't be detected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// // SAFETY: Nothing truncates the file while it is uploaded.
+    /// unsafe { form.map_files(16 * 1024 * 1024) };
+    ///
+    /// form.add_file("input", format!("../{}", file!())).unwrap();
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map_files(&mut self, min_len: u64) {
+        self.map_min_len = Some(min_len);
+    }
+
+    /// Limits the length of the content of each part, as it is written.
+    /// The body fails with `Error::LimitExceeded` when a part goes over it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.set_max_part_len(10 * 1024 * 1024);
+    /// ```
+    pub fn set_max_part_len(&mut self, max: u64) {
+        self.max_part_len = Some(max);
+    }
+
+    /// Limits the length of the whole body. The body fails with
+    /// `Error::LimitExceeded` when it goes over it.
+    pub fn set_max_body_len(&mut self, max: u64) {
+        self.max_body_len = Some(max);
+    }
+
+    /// Sets how the form is serialized. Forms are multipart unless this is
+    /// changed. With `FormEncoding::Auto`, a form that only has text parts
+    /// is sent as `application/x-www-form-urlencoded`, which is much
+    /// smaller, so forms can be built the same way either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart::{self, FormEncoding};
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.set_encoding(FormEncoding::Auto);
+    /// form.add_text("text", "Hello World!");
+    ///
+    /// assert_eq!(form.content_type(), "application/x-www-form-urlencoded");
+    ///
+    /// form.add_reader("input", std::io::Cursor::new("Hello World!"));
+    ///
+    /// assert!(form.content_type().starts_with("multipart/form-data"));
+    /// ```
+    pub fn set_encoding(&mut self, encoding: FormEncoding) {
+        self.encoding = encoding;
+    }
+
+    /// Returns true if the form is sent urlencoded.
+    fn is_urlencoded(&self) -> bool {
+        match self.encoding {
+            FormEncoding::Multipart => false,
+            FormEncoding::UrlEncoded => true,
+            FormEncoding::Auto => {
+                self.subtype.eq_ignore_ascii_case("form-data")
+                    && self
+                        .parts
+                        .iter()
+                        .all(|part| part.urlencoded_text().is_some())
+            }
+        }
+    }
+
+    /// Serializes the form as `application/x-www-form-urlencoded`, whatever
+    /// encoding is set. Fails if the form has a part other than text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_text("text", "Hello World!");
+    /// form.add_text("more", "a&b");
+    ///
+    /// assert_eq!(form.to_urlencoded().unwrap(), "text=Hello+World%21&more=a%26b");
+    /// ```
+    pub fn to_urlencoded(&self) -> Result<String, Error> {
+        urlencoded::encode(&self.parts, self.charset)
+    }
+
+    /// Writes the whole body into a vector, which makes it easy to compare
+    /// with an expected body in a test.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::{client::multipart, test_util::FixedBoundary};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let mut form = multipart::Form::new::<FixedBoundary>();
+    ///
+    /// form.add_text("text", "Hello World!");
+    ///
+    /// let body = form.encode_to_vec().await.unwrap();
+    ///
+    /// assert!(body.starts_with(b"--boundary\r\n"));
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "test-util"))]
+    pub async fn encode_to_vec(self) -> Result<Vec<u8>, Error> {
+        use futures_util::TryStreamExt;
+
+        Body::from(self)
+            .try_concat()
+            .await
+            .map(|body| body.to_vec())
+    }
+
+    /// Returns the curl arguments that send the same parts, like
+    /// `["-F", "file=@test.txt;type=text/plain;filename=test.txt"]`, so a
+    /// form can be replayed from a shell.
+    ///
+    /// Text that curl would read as a file, or options, is passed with
+    /// `--form-string`, or quoted. Parts read from a reader don't have a
+    /// path, and are read from standard input with `@-`. The form's
+    /// boundary, charset, and trailers, and the compression, and checksums
+    /// of parts have no curl equivalent, and aren't written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_text("text", "Hello World!");
+    /// form.add_text("at", "@home");
+    ///
+    /// assert_eq!(
+    ///     form.to_curl_args(),
+    ///     ["-F", "text=Hello World!", "--form-string", "at=@home"]
+    /// );
+    /// ```
+    pub fn to_curl_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        for part in self.parts.iter() {
+            curl::write_args(part, &mut args);
+        }
+
+        args
+    }
+
+    /// Adds the parts described by curl `-F`, `--form`, and `--form-string`
+    /// arguments, like the ones in a customer's curl command. Other
+    /// arguments are skipped, so a whole command line can be passed. Files
+    /// named with `@` are read when the form is written, and files named
+    /// with `<` are read now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_curl_args([
+    ///     "-X",
+    ///     "POST",
+    ///     "-F",
+    ///     "text=\"Hello; World!\";type=text/html",
+    ///     "https://example.com",
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(form.len(), 1);
+    /// ```
+    pub fn add_curl_args<I, S>(&mut self, args: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.parts.extend(curl::parse_args(args)?);
+
+        Ok(())
+    }
+
+    /// Creates a form from curl arguments, with a random boundary. See
+    /// [`Form::add_curl_args`].
+    pub fn from_curl_args<I, S>(args: I) -> Result<Form<'a>, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut form = Form::default();
+
+        form.add_curl_args(args)?;
+
+        Ok(form)
+    }
+
+    /// Declares the charset of the form's text parts. A `_charset_` part
+    /// with the name of the charset is written first, and the text parts
+    /// that don't have a charset are sent with a `charset` parameter, and
+    /// encoded with it.
+    ///
+    /// [See RFC7578 4.6](https://tools.ietf.org/html/rfc7578#section-4.6).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart::{self, Charset};
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.set_charset(Charset::UTF_8);
+    /// form.add_text("text", "Hello World!");
+    /// ```
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = Some(charset);
+    }
+
+    /// Sets the multipart subtype, like `mixed`, or `alternative`. Parts of a
+    /// body that isn't `form-data` are written without a Content-Disposition
+    /// header, and with any headers set with `Part::with_header`.
+    ///
+    /// [See RFC2046 5.1](https://tools.ietf.org/html/rfc2046#section-5.1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.set_subtype("mixed");
+    ///
+    /// assert!(form.content_type().starts_with("multipart/mixed; boundary="));
+    /// ```
+    pub fn set_subtype<S>(&mut self, subtype: S)
+    where
+        S: Into<String>,
+    {
+        self.subtype = subtype.into();
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Form<'static> {
+    /// Reads the parts added with `add_reader`, `add_reader_file`, or
+    /// `add_file` on tokio's blocking thread pool instead of the executor
+    /// thread. This applies to parts added before and after calling it.
+    ///
+    /// Each reader is read in chunks sent over a channel that holds up to
+    /// `capacity` chunks, so a reader never gets far ahead of the body. The
+    /// body must be polled from within a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart;
+    /// use std::io::Cursor;
+    ///
+    /// let mut form = multipart::Form::default();
+    ///
+    /// form.add_reader("input", Cursor::new("Hello World!"));
+    /// form.offload_sync_readers(4);
+    /// ```
+    pub fn offload_sync_readers(&mut self, capacity: usize) {
+        self.offload = Some(BlockingOffload::new(capacity));
+    }
+}
+
+impl<'a> From<Form<'a>> for Body<'a> {
+    /// Turns a `Form` into a multipart `Body`.
+    fn from(form: Form<'a>) -> Self {
+        if form.is_urlencoded() {
+            let pending = urlencoded::encode(&form.parts, form.charset)
+                .map(|encoded| BytesMut::from(encoded.as_bytes()));
+
+            let trace = BodyTrace::new(urlencoded::CONTENT_TYPE, form.parts.len());
+
+            return Body {
+                buf: BytesMut::new(),
+                pending: Some(pending),
+                current: None,
+                parts: Vec::new().into_iter().peekable(),
+                form_data: false,
+                boundary: form.boundary,
+                #[cfg(feature = "tokio")]
+                offload: None,
+                checksums: vec![],
+                trailers: form.trailers,
+                computed_trailers: form.computed_trailers,
+                bytes_written: 0,
+                rate_limiter: form.rate_limiter,
+                throttled: None,
+                delay: None,
+                abort: form.abort,
+                sent_parts: vec![],
+                done: false,
+                delimiter: Finder::new(&[]).into_owned(),
+                part_index: 0,
+                max_part_len: None,
+                max_body_len: form.max_body_len,
+                trace,
+                metrics: form.metrics,
+                timeouts: form.timeouts,
+                idle: None,
+                deadline: None,
+                #[cfg(feature = "mmap")]
+                map_min_len: None,
+            };
+        }
+
+        let content_type = form.content_type();
+        let parts = match form.charset {
+            Some(charset) => {
+                let mut parts = Vec::with_capacity(form.parts.len() + 1);
+
+                parts.push(Part::text("_charset_", charset.name()));
+                parts.extend(form.parts.into_iter().map(|part| part.encode_text(charset)));
+                parts
+            }
+            None => form.parts,
+        };
+        let delimiter = Finder::new(format!("\r\n--{}", form.boundary).as_bytes()).into_owned();
+        let trace = BodyTrace::new(&content_type, parts.len());
+
+        Body {
+            buf: BytesMut::with_capacity(2048),
+            pending: None,
+            current: None,
+            parts: parts.into_iter().peekable(),
+            form_data: form.subtype.eq_ignore_ascii_case("form-data"),
+            boundary: form.boundary,
+            #[cfg(feature = "tokio")]
+            offload: form.offload,
+            checksums: vec![],
+            trailers: form.trailers,
+            computed_trailers: form.computed_trailers,
+            bytes_written: 0,
+            rate_limiter: form.rate_limiter,
+            throttled: None,
+            delay: None,
+            abort: form.abort,
+            sent_parts: vec![],
+            done: false,
+            delimiter,
+            part_index: 0,
+            max_part_len: form.max_part_len,
+            max_body_len: form.max_body_len,
+            trace,
+            metrics: form.metrics,
+            timeouts: form.timeouts,
+            idle: None,
+            deadline: None,
+            #[cfg(feature = "mmap")]
+            map_min_len: form.map_min_len,
+        }
+    }
+}
+
+/// One part of a body delimited by a boundary line.
+///
+/// Parts are usually added with one of the `Form::add_*` methods. Building a
+/// part directly allows setting options that apply to a single part, and
+/// adding it with [`Form::add_part`].
+///
+/// [See RFC2046 5.1](https://tools.ietf.org/html/rfc2046#section-5.1).
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::{self, Part, TransferEncoding};
+/// use std::io::Cursor;
+///
+/// let mut form = multipart::Form::default();
+/// let part = Part::reader("input", Cursor::new("Hello World!"))
+///     .with_filename("hello.txt")
+///     .with_mime(mime::TEXT_PLAIN)
+///     .with_transfer_encoding(TransferEncoding::Base64);
+///
+/// form.add_part(part);
+/// ```
+pub struct Part<'a> {
+    inner: Inner<'a>,
+
+    /// The name of the form field.
+    name: String,
+
+    /// An optional filename, which is usually set for file data.
+    filename: Option<String>,
+
+    /// Each part can include a Content-Type header field. If this
+    /// is not specified, it defaults to "text/plain", or
+    /// "application/octet-stream" for file data.
+    ///
+    /// [See](https://tools.ietf.org/html/rfc7578#section-4.4)
+    content_type: Mime,
+
+    /// An optional compression applied to the content while it is written,
+    /// before the transfer encoding.
+    compression: Option<Compression>,
+
+    /// An optional encoding applied to the content while it is written.
+    ///
+    /// [See](https://tools.ietf.org/html/rfc7578#section-4.7)
+    transfer_encoding: Option<TransferEncoding>,
+
+    /// Checksums computed over the content while it is written.
+    checksums: Vec<Checksum>,
+
+    /// Other headers written with the part.
+    headers: HeaderMap,
+}
+
+impl<'a> Part<'a> {
+    /// Internal method to build a new Part instance. Sets the content-type,
+    /// and the disposition parameters for name, and optionally for filename.
+    ///
+    /// Per [4.3](https://tools.ietf.org/html/rfc7578#section-4.3), if multiple
+    /// files need to be specified for one form field, they can all be specified
+    /// with the same name parameter.
+    fn new<N, F>(inner: Inner<'a>, name: N, mime: Option<Mime>, filename: Option<F>) -> Part<'a>
+    where
+        N: Display,
+        F: Display,
+    {
+        let content_type = mime.unwrap_or_else(|| inner.default_content_type());
+
+        Part {
+            inner,
+            name: name.to_string(),
+            filename: filename.map(|filename| filename.to_string()),
+            content_type,
+            compression: None,
+            transfer_encoding: None,
+            checksums: vec![],
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Creates a text part.
+    pub fn text<N, T>(name: N, text: T) -> Part<'a>
+    where
+        N: Display,
+        T: Into<String>,
+    {
+        Part::new::<_, String>(Inner::Text(text.into()), name, None, None)
+    }
+
+    /// Creates a readable part.
+    pub fn reader<N, R>(name: N, read: R) -> Part<'a>
+    where
+        N: Display,
+        R: 'a + Read + Send + Unpin,
+    {
+        Part::new::<_, String>(Inner::Read(Box::new(read)), name, None, None)
+    }
+
+    /// Creates an async readable part.
+    pub fn async_reader<N, R>(name: N, read: R) -> Part<'a>
+    where
+        N: Display,
+        R: 'a + AsyncRead + Send + Unpin,
+    {
+        Part::new::<_, String>(Inner::AsyncRead(Box::new(read)), name, None, None)
+    }
+
+    /// Creates a file part, and attempts to derive the mime type. The file is
+    /// opened when the body reaches the part.
+    pub fn file<N, P>(name: N, path: P) -> io::Result<Part<'a>>
+    where
+        N: Display,
+        P: AsRef<Path>,
+    {
+        Part::from_path(name, path, None, Inner::File)
+    }
+
+    /// Internal method to build a file part, after checking that the path is
+    /// a file.
+    fn from_path<N, P>(
+        name: N,
+        path: P,
+        mime: Option<Mime>,
+        inner: fn(PathBuf) -> Inner<'a>,
+    ) -> io::Result<Part<'a>>
+    where
+        N: Display,
+        P: AsRef<Path>,
+    {
+        let mime = mime.or_else(|| mime_guess::from_path(&path).first());
+
+        // Early return if the file metadata could not be accessed. The file
+        // itself isn't opened until the body reaches its part.
+        let meta = path.as_ref().metadata()?;
+
+        if !meta.is_file() {
+            // If the path is not a file, it can't be uploaded because there
+            // is no content.
+
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected a file not directory",
+            ));
+        }
+
+        Ok(Part::new(
+            inner(path.as_ref().to_path_buf()),
+            name,
+            mime,
+            Some(path.as_ref().as_os_str().to_string_lossy()),
+        ))
+    }
+
+    /// Returns the name of the form field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the filename sent with the part, if it has one.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the mime type of the part.
+    pub fn content_type(&self) -> &Mime {
+        &self.content_type
+    }
+
+    /// Returns where the content of the part comes from.
+    pub(crate) fn source(&self) -> Source<'_> {
+        match self.inner {
+            Inner::Text(ref text) => Source::Text(text),
+            Inner::File(ref path) => Source::File(path),
+            #[cfg(any(feature = "tokio", feature = "async-std"))]
+            Inner::AsyncFile(ref path) => Source::File(path),
+            Inner::Read(_) | Inner::AsyncRead(_) => Source::Stream,
+        }
+    }
+
+    /// Returns the text of a part that can be sent urlencoded, which is a
+    /// `text/plain` text part without a filename, or anything that changes
+    /// how it is written.
+    pub(crate) fn urlencoded_text(&self) -> Option<&str> {
+        match self.inner {
+            Inner::Text(ref text)
+                if self.content_type == mime::TEXT_PLAIN
+                    && self.filename.is_none()
+                    && self.compression.is_none()
+                    && self.transfer_encoding.is_none()
+                    && self.checksums.is_empty()
+                    && self.headers.is_empty() =>
+            {
+                Some(text)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the other headers written with the part.
+    pub(crate) fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the path of a file that can be mapped, which is one whose
+    /// content is written as it is.
+    #[cfg(feature = "mmap")]
+    fn mappable_path(&self) -> Option<&Path> {
+        if self.compression.is_some() || self.transfer_encoding.is_some() {
+            return None;
+        }
+
+        match self.inner {
+            Inner::File(ref path) => Some(path),
+            #[cfg(any(feature = "tokio", feature = "async-std"))]
+            Inner::AsyncFile(ref path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the transfer encoding applied to the content, if any.
+    pub(crate) fn transfer_encoding(&self) -> Option<TransferEncoding> {
+        self.transfer_encoding
+    }
+
+    /// Returns the length of the content as it is written, if it is known
+    /// without reading it. This is the case for text, and files, unless the
+    /// part is compressed, or quoted-printable encoded.
+    ///
+    /// The length of a file is read when this is called, and may change
+    /// before the part is written.
+    pub fn known_length(&self) -> Option<u64> {
+        let len = match self.inner {
+            Inner::Text(ref text) => text.len() as u64,
+            Inner::File(ref path) => path.metadata().ok()?.len(),
+            #[cfg(any(feature = "tokio", feature = "async-std"))]
+            Inner::AsyncFile(ref path) => path.metadata().ok()?.len(),
+            Inner::Read(_) | Inner::AsyncRead(_) => return None,
+        };
+
+        if self.compression.is_some() {
+            return None;
+        }
+
+        match self.transfer_encoding {
+            Some(encoding) => encoding.encoded_len(len),
+            None => Some(len),
+        }
+    }
+
+    /// Renames the form field the part is sent as.
+    #[cfg(any(feature = "graphql", feature = "s3"))]
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Sets the filename sent with the part.
+    pub fn with_filename<F>(mut self, filename: F) -> Part<'a>
+    where
+        F: Into<String>,
+    {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets the mime type of the part.
+    pub fn with_mime(mut self, mime: Mime) -> Part<'a> {
+        self.content_type = mime;
+        self
+    }
+
+    /// Compresses the content of the part while it is written, and sets the
+    /// Content-Encoding header. The receiving server has to support the
+    /// compression for the part.
+    pub fn with_compression(mut self, compression: Compression) -> Part<'a> {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Encodes the content of the part with the specified encoding while it
+    /// is written, and sets the Content-Transfer-Encoding header.
+    pub fn with_transfer_encoding(mut self, encoding: TransferEncoding) -> Part<'a> {
+        self.transfer_encoding = Some(encoding);
+        self
+    }
+
+    /// Computes a checksum over the content of the part while it is written.
+    /// This can be called more than once to compute several checksums.
+    ///
+    /// The actix body doesn't send trailers, so a `Checksum::trailer` digest
+    /// isn't sent with it. It can still be read from `Body::checksums` once
+    /// the body is written, or sent with `Checksum::field` instead.
+    pub fn with_checksum(mut self, checksum: Checksum) -> Part<'a> {
+        self.checksums.push(checksum);
+        self
+    }
+
+    /// Adds a header that is written with the part, like `Content-ID`.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Part<'a> {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Encodes a text/plain part that doesn't declare a charset with the
+    /// form's charset.
+    fn encode_text(mut self, charset: Charset) -> Part<'a> {
+        if self.content_type != mime::TEXT_PLAIN {
+            return self;
+        }
+
+        if let Inner::Text(text) = self.inner {
+            self.inner = if charset == Charset::UTF_8 {
+                Inner::Text(text)
+            } else {
+                Inner::AsyncRead(Box::new(Cursor::new(charset.encode(&text).into_owned())))
+            };
+
+            // The name of a charset is always a valid parameter value.
+            if let Ok(mime) = format!("text/plain; charset={}", charset.name()).parse() {
+                self.content_type = mime;
+            }
+        }
+
+        self
+    }
+
+    /// Turns the part into a reader of its content, as it is written to the
+    /// body.
+    fn into_reader(
+        self,
+        #[cfg(feature = "tokio")] offload: Option<BlockingOffload<'a>>,
+    ) -> Result<Box<dyn 'a + AsyncRead + Send + Unpin>, Error> {
+        #[cfg(feature = "tokio")]
+        let read = self.inner.into_reader(offload)?;
+        #[cfg(not(feature = "tokio"))]
+        let read = self.inner.into_reader()?;
+
+        #[cfg(feature = "__compression")]
+        let read: Box<dyn 'a + AsyncRead + Send + Unpin> = match self.compression {
+            Some(compression) => Box::new(EncodedRead::new(
+                read,
+                compression
+                    .encoder()
+                    .map_err(|source| Error::ContentRead { part: None, source })?,
+            )),
+            None => read,
+        };
+
+        Ok(match self.transfer_encoding {
+            Some(encoding) => Box::new(EncodedRead::new(read, encoding.encoder())),
+            None => read,
+        })
+    }
+
+    /// Returns the Content-Disposition header value.
+    ///
+    /// [See](https://tools.ietf.org/html/rfc7578#section-4.2).
+    fn content_disposition(&self) -> String {
+        // `name` disposition parameter is required. It should correspond to the
+        // name of a form field.
+        //
+        // [See 4.2](https://tools.ietf.org/html/rfc7578#section-4.2)
+        //
+        let mut disposition = format!("form-data; name=\"{}\"", self.name);
+
+        // `filename` can be supplied for files, but is totally optional.
+        //
+        // [See 4.2](https://tools.ietf.org/html/rfc7578#section-4.2)
+        //
+        if let Some(ref filename) = self.filename {
+            disposition.push_str(&format!("; filename=\"{}\"", filename));
+        }
+
+        disposition
+    }
+}
+
+enum Inner<'a> {
+    /// The `Read` and `AsyncRead` variants captures multiple cases.
+    ///
+    ///   * The first is it supports uploading a file, which is explicitly
+    ///     described in RFC 7578.
+    ///
+    ///   * The second (which is not described by RFC 7578), is it can handle
+    ///     arbitrary input streams (for example, a server response).
+    ///     Any arbitrary input stream is automatically considered a file,
+    ///     and assigned the corresponding content type if not explicitly
+    ///     specified.
+    Read(Box<dyn 'a + Read + Send + Unpin>),
+
+    AsyncRead(Box<dyn 'a + AsyncRead + Send + Unpin>),
+
+    /// The `File` variant is a file that is opened when the part is written.
+    File(PathBuf),
+
+    /// The `AsyncFile` variant is a file that is read by an async runtime.
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    AsyncFile(PathBuf),
+
+    /// The `String` variant handles "text/plain" form data payloads.
+    Text(String),
+}
+
+/// Where the content of a part comes from.
+pub(crate) enum Source<'p> {
+    Text(&'p str),
+
+    File(&'p Path),
+
+    /// A reader, which can only be read once.
+    Stream,
+}
+
+impl<'a> Inner<'a> {
+    /// Returns the default Content-Type header value as described in section 4.4.
+    ///
+    /// [See](https://tools.ietf.org/html/rfc7578#section-4.4)
+    fn default_content_type(&self) -> Mime {
+        match *self {
+            Inner::Text(_) => mime::TEXT_PLAIN,
+            _ => mime::APPLICATION_OCTET_STREAM,
+        }
+    }
+
+    /// Turns the part content into a reader. Files are only opened here, once
+    /// their part is reached, so a body holds one file open at a time.
+    ///
+    /// The file is closed when the reader is dropped, after the part has been
+    /// written.
+    fn into_reader(
+        self,
+        #[cfg(feature = "tokio")] offload: Option<BlockingOffload<'a>>,
+    ) -> Result<Box<dyn 'a + AsyncRead + Send + Unpin>, Error> {
+        #[cfg(feature = "tokio")]
+        if let Some(offload) = offload {
+            let read: Box<dyn 'a + Read + Send + Unpin> = match self {
+                Inner::Read(read) => read,
+                Inner::File(path) => match File::open(&path) {
+                    Ok(f) => Box::new(f),
+                    Err(source) => {
+                        return Err(Error::FileOpen {
+                            part: None,
+                            path,
+                            source,
+                        })
+                    }
+                },
+                inner => return inner.into_reader(None),
+            };
+
+            return offload
+                .spawn(read)
+                .map_err(|source| Error::ContentRead { part: None, source });
+        }
+
+        match self {
+            Inner::Read(read) => Ok(Box::new(AllowStdIo::new(read))),
+            Inner::AsyncRead(read) => Ok(read),
+            Inner::File(path) => match File::open(&path) {
+                Ok(f) => Ok(Box::new(AllowStdIo::new(f))),
+                Err(source) => Err(Error::FileOpen {
+                    part: None,
+                    path,
+                    source,
+                }),
+            },
+            #[cfg(any(feature = "tokio", feature = "async-std"))]
+            Inner::AsyncFile(path) => match File::open(&path) {
+                Ok(f) => Ok(runtime::async_file(f)),
+                Err(source) => Err(Error::FileOpen {
+                    part: None,
+                    path,
+                    source,
+                }),
+            },
+            Inner::Text(s) => Ok(Box::new(Cursor::new(s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Body, Form, Part};
+    use crate::{
+        charset::Charset,
+        encoding::TransferEncoding,
+        error::{Error, Limit, PartContext},
+        test_util::FixedBoundary,
+        urlencoded::FormEncoding,
+    };
+    use bytes::BytesMut;
+    use futures_util::TryStreamExt;
+    use http::{header::CONTENT_TYPE, Request};
+    use std::{
+        io::Cursor,
+        path::{Path, PathBuf},
+    };
+
+    async fn form_output(form: Form<'_>) -> String {
+        let result: Result<BytesMut, Error> = Body::from(form).try_concat().await;
+
+        assert!(result.is_ok());
+
+        let bytes = result.unwrap();
+        let data = std::str::from_utf8(bytes.as_ref()).unwrap();
+
+        data.into()
+    }
+
+    fn test_file_path() -> PathBuf {
+        // common/src/data/test.txt
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("data")
+            .join("test.txt")
+    }
+
+    #[tokio::test]
+    async fn add_text_returns_expected_result() {
+        let mut form = Form::default();
+
+        form.add_text("test", "Hello World!");
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("Hello World!"));
+    }
+
+    #[tokio::test]
+    async fn add_reader_returns_expected_result() {
+        let bytes = Cursor::new("Hello World!");
+        let mut form = Form::default();
+
+        form.add_reader("input", bytes);
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("Hello World!"));
+    }
+
+    #[tokio::test]
+    async fn add_file_returns_expected_result() {
+        let mut form = Form::default();
+
+        assert!(form.add_file("test_file.txt", test_file_path()).is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("This is a test file!"));
+        assert!(data.contains("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn add_file_with_mime_returns_expected_result() {
+        let mut form = Form::default();
+
+        assert!(form
+            .add_file_with_mime("test_file.txt", test_file_path(), mime::TEXT_CSV)
+            .is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("This is a test file!"));
+        assert!(data.contains("text/csv"));
+    }
+
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    #[tokio::test]
+    async fn add_file_async_returns_expected_result() {
+        let mut form = Form::default();
+
+        assert!(form
+            .add_file_async("test_file.txt", test_file_path())
+            .is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("This is a test file!"));
+        assert!(data.contains("text/plain"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn offload_sync_readers_returns_expected_result() {
+        let text = "Hello World!".repeat(2048);
+        let mut form = Form::default();
+
+        form.offload_sync_readers(2);
+        form.add_reader("input", Cursor::new(text.clone()));
+        assert!(form.add_file("test_file.txt", test_file_path()).is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains(&text));
+        assert!(data.contains("This is a test file!"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn offload_sync_readers_returns_read_error() {
+        struct FailingRead;
+
+        impl std::io::Read for FailingRead {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read failed"))
+            }
+        }
+
+        let mut form = Form::default();
+
+        form.offload_sync_readers(2);
+        form.add_reader("input", FailingRead);
+
+        let result: Result<BytesMut, Error> = Body::from(form).try_concat().await;
+
+        assert!(matches!(result, Err(Error::ContentRead { .. })));
+    }
+
+    #[cfg(feature = "directory")]
+    #[tokio::test]
+    async fn add_directory_returns_expected_result() {
+        use crate::directory::DirectoryOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("upload");
+
+        std::fs::create_dir_all(root.join("nested").join("skip")).unwrap();
+        std::fs::write(root.join("a.txt"), "File A").unwrap();
+        std::fs::write(root.join("b.csv"), "File B").unwrap();
+        std::fs::write(root.join("nested").join("c.txt"), "File C").unwrap();
+        std::fs::write(root.join("nested").join("skip").join("d.txt"), "File D").unwrap();
+
+        let mut form = Form::default();
+        let options = DirectoryOptions::new()
+            .include("*.txt")
+            .exclude("nested/skip");
+
+        assert!(form.add_directory("files", &root, options).is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("name=\"files\"; filename=\"upload/a.txt\""));
+        assert!(data.contains("name=\"files\"; filename=\"upload/nested/c.txt\""));
+        assert!(data.contains("File A"));
+        assert!(data.contains("File C"));
+        assert!(!data.contains("File B"));
+        assert!(!data.contains("File D"));
+    }
+
+    #[cfg(feature = "directory")]
+    #[tokio::test]
+    async fn add_directory_without_recursion_skips_subdirectories() {
+        use crate::directory::DirectoryOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "File A").unwrap();
+        std::fs::write(dir.path().join("nested").join("b.txt"), "File B").unwrap();
+
+        let mut form = Form::default();
+        let options = DirectoryOptions::new().recursive(false);
+
+        assert!(form.add_directory("files", dir.path(), options).is_ok());
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("File A"));
+        assert!(!data.contains("File B"));
+    }
+
+    #[cfg(feature = "directory")]
+    #[test]
+    fn add_directory_rejects_file() {
+        use crate::directory::DirectoryOptions;
+
+        let mut form = Form::default();
+
+        assert!(form
+            .add_directory("files", test_file_path(), DirectoryOptions::new())
+            .is_err());
+    }
+
+    #[cfg(feature = "directory")]
+    #[tokio::test]
+    async fn add_directory_open_error_is_returned_by_body() {
+        use crate::directory::DirectoryOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("upload");
+
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("a.txt"), "File A").unwrap();
+
+        let mut form = Form::default();
+
+        assert!(form
+            .add_directory("files", &root, DirectoryOptions::new())
+            .is_ok());
+
+        std::fs::remove_file(root.join("a.txt")).unwrap();
+
+        let result: Result<BytesMut, Error> = Body::from(form).try_concat().await;
+
+        assert!(matches!(result, Err(Error::FileOpen { .. })));
+    }
+
+    #[tokio::test]
+    async fn add_file_opens_file_when_part_is_reached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+
+        std::fs::write(&path, "File A").unwrap();
+
+        let mut form = Form::default();
+
+        assert!(form.add_file("file", &path).is_ok());
+
+        // The file is read when the body is written, not when it is added.
+        std::fs::write(&path, "File B").unwrap();
+
+        let data = form_output(form).await;
+
+        assert!(data.contains("File B"));
+    }
+
+    #[tokio::test]
+    async fn add_file_open_error_is_returned_by_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+
+        std::fs::write(&path, "File A").unwrap();
+
+        let mut form = Form::default();
+
+        assert!(form.add_file("file", &path).is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+
+        let result: Result<BytesMut, Error> = Body::from(form).try_concat().await;
+
+        match result {
+            Err(Error::FileOpen { path: err_path, .. }) => assert_eq!(err_path, path),
+            _ => panic!("expected a file open error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_form_body_stream() {
+        let mut form = Form::new::<FixedBoundary>();
+        // Text fields
+        form.add_text("name1", "value1");
+        form.add_text("name2", "value2");
+
+        // Reader field
+        form.add_reader("input", Cursor::new("Hello World!"));
+
+        let result: BytesMut = Body::from(form).try_concat().await.unwrap();
+
+        assert_eq!(
+            result.as_ref(),
+            [
+                b"--boundary\r\n".as_ref(),
+                b"content-type: text/plain\r\n".as_ref(),
+                b"content-disposition: form-data; name=\"name1\"\r\n".as_ref(),
+                b"\r\n".as_ref(),
+                b"value1\r\n".as_ref(),
+                b"--boundary\r\n".as_ref(),
+                b"content-type: text/plain\r\n".as_ref(),
+                b"content-disposition: form-data; name=\"name2\"\r\n".as_ref(),
+                b"\r\n".as_ref(),
+                b"value2\r\n".as_ref(),
+                b"--boundary\r\n".as_ref(),
+                b"content-type: application/octet-stream\r\n".as_ref(),
+                b"content-disposition: form-data; name=\"input\"\r\n".as_ref(),
+                b"\r\n".as_ref(),
+                b"Hello World!\r\n".as_ref(),
+                b"--boundary--\r\n".as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<u8>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn add_part_with_transfer_encoding() {
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_part(
+            Part::reader("input", Cursor::new("Hello World!"))
+                .with_filename("hello.txt")
+                .with_transfer_encoding(TransferEncoding::Base64),
+        );
+
+        let data = form_output(form).await;
+
+        assert_eq!(
+            data,
+            "--boundary\r\n\
+             content-type: application/octet-stream\r\n\
+             content-disposition: form-data; name=\"input\"; filename=\"hello.txt\"\r\n\
+             content-transfer-encoding: base64\r\n\
+             \r\n\
+             SGVsbG8gV29ybGQh\r\n\
+             --boundary--\r\n"
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn add_part_with_compression() {
+        use crate::compression::Compression;
+
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_part(
+            Part::text("text", "Hello World!")
+                .with_compression(Compression::Gzip)
+                .with_transfer_encoding(TransferEncoding::Base64),
+        );
+
+        let data = form_output(form).await;
+        let content = data
+            .split("\r\n\r\n")
+            .nth(1)
+            .and_then(|content| content.strip_suffix("\r\n--boundary--\r\n"))
+            .unwrap();
+        let compressed = TransferEncoding::Base64.decode(content.as_bytes()).unwrap();
+
+        assert!(data.contains("content-encoding: gzip\r\n"));
+        assert_eq!(
+            Compression::Gzip.decode(&compressed, u64::MAX).unwrap(),
+            b"Hello World!".to_vec()
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[tokio::test]
+    async fn add_part_with_checksum_field() {
+        use crate::checksum::{Checksum, ChecksumAlgorithm, DigestFormat};
+
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_part(
+            Part::reader("input", Cursor::new("Hello World!")).with_checksum(
+                Checksum::field(ChecksumAlgorithm::Sha256, "input-sha256")
+                    .with_format(DigestFormat::Hex),
+            ),
+        );
+        form.add_text("text", "value");
+
+        let mut body = Body::from(form);
+        let mut data = BytesMut::new();
+
+        while let Some(chunk) = body.try_next().await.unwrap() {
+            data.extend_from_slice(&chunk);
+        }
+
+        assert_eq!(
+            std::str::from_utf8(&data).unwrap(),
+            "--boundary\r\n\
+             content-type: application/octet-stream\r\n\
+             content-disposition: form-data; name=\"input\"\r\n\
+             \r\n\
+             Hello World!\r\n\
+             --boundary\r\n\
+             content-type: text/plain\r\n\
+             content-disposition: form-data; name=\"input-sha256\"\r\n\
+             \r\n\
+             7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069\r\n\
+             --boundary\r\n\
+             content-type: text/plain\r\n\
+             content-disposition: form-data; name=\"text\"\r\n\
+             \r\n\
+             value\r\n\
+             --boundary--\r\n"
+        );
+        assert_eq!(body.checksums().len(), 1);
+        assert_eq!(body.checksums()[0].name(), "input");
+        assert_eq!(
+            body.checksums()[0].to_base64(),
+            "f4OxZX/x/FO5LcGBSKHWXfwtSx+j1ncoSt3SABJtkGk="
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[tokio::test]
+    async fn line_break_in_checksum_field_name_is_invalid_header() {
+        use crate::checksum::{Checksum, ChecksumAlgorithm};
+
+        let mut form = Form::default();
+
+        form.add_part(
+            Part::text("text", "Hello World!").with_checksum(Checksum::field(
+                ChecksumAlgorithm::Sha256,
+                "text-sha256\r\nx-injected: 1",
+            )),
+        );
+
+        assert!(matches!(
+            Body::from(form).try_concat().await,
+            Err(Error::InvalidHeader { part, .. }) if part.index() == 0
+        ));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[tokio::test]
+    async fn checksum_field_without_disposition_outside_form_data() {
+        use crate::checksum::{Checksum, ChecksumAlgorithm, DigestFormat};
+
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.set_subtype("mixed");
+        form.add_part(
+            Part::text("ignored", "Hello World!").with_checksum(
+                Checksum::field(ChecksumAlgorithm::Sha256, "ignored-sha256")
+                    .with_format(DigestFormat::Hex),
+            ),
+        );
+
+        assert_eq!(
+            form_output(form).await,
+            "--boundary\r\n\
+             content-type: text/plain\r\n\
+             \r\n\
+             Hello World!\r\n\
+             --boundary\r\n\
+             content-type: text/plain\r\n\
+             \r\n\
+             7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069\r\n\
+             --boundary--\r\n"
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[tokio::test]
+    async fn add_part_with_checksum_trailer() {
+        use crate::checksum::{Checksum, ChecksumAlgorithm};
+        use http::{header::HeaderName, Request};
+
+        let name = HeaderName::from_static("x-checksum-sha256");
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_part(
+            Part::reader("input", Cursor::new("Hello World!"))
+                .with_checksum(Checksum::trailer(ChecksumAlgorithm::Sha256, name.clone())),
+        );
+
+        let req = form.set_body::<Body>(Request::builder()).unwrap();
+
+        assert_eq!(req.headers()["trailer"], "x-checksum-sha256");
+
+        let mut body = req.into_body();
+
+        assert!(body.trailers().is_empty());
+
+        while body.try_next().await.unwrap().is_some() {}
+
+        assert_eq!(
+            body.trailers()[&name],
+            "f4OxZX/x/FO5LcGBSKHWXfwtSx+j1ncoSt3SABJtkGk="
+        );
+    }
+
+    #[tokio::test]
+    async fn add_trailer_returns_trailers_after_body() {
+        use http::{
+            header::{HeaderName, HeaderValue},
+            Request,
+        };
+
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_text("text", "Hello World!");
+        form.add_trailer(
+            HeaderName::from_static("x-static"),
+            HeaderValue::from_static("value"),
+        );
+        form.add_computed_trailer(HeaderName::from_static("x-length"), |body| {
+            HeaderValue::from(body.bytes_written())
+        });
+
+        let req = form.set_body::<Body>(Request::builder()).unwrap();
+
+        assert_eq!(req.headers()["trailer"], "x-static, x-length");
+
+        let mut body = req.into_body();
+        let mut len = 0;
+
+        assert!(!body.trailers().contains_key("x-length"));
+
+        while let Some(chunk) = body.try_next().await.unwrap() {
+            len += chunk.len();
+        }
+
+        assert_eq!(body.trailers()["x-static"], "value");
+        assert_eq!(body.trailers()["x-length"], len.to_string().as_str());
+    }
+
+    #[tokio::test]
+    async fn test_content_type_header_format() {
+        use http::Request;
 
-        let data = form_output(form).await;
+        let mut form = Form::new::<FixedBoundary>();
+        // Text fields
+        form.add_text("name1", "value1");
+        form.add_text("name2", "value2");
 
-        assert!(data.contains("Hello World!"));
+        let builder = Request::builder();
+        let body = form.set_body::<Body>(builder).unwrap();
+
+        assert_eq!(
+            body.headers().get("Content-Type").unwrap().as_bytes(),
+            b"multipart/form-data; boundary=boundary",
+        )
     }
 
     #[tokio::test]
-    async fn add_reader_returns_expected_result() {
-        let bytes = Cursor::new("Hello World!");
+    async fn set_charset_writes_charset_part() {
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.set_charset(Charset::UTF_8);
+        form.add_text("text", "Hello World!");
+        form.add_reader("input", Cursor::new("Hello World!"));
+
+        assert_eq!(
+            form_output(form).await,
+            "--boundary\r\n\
+             content-type: text/plain\r\n\
+             content-disposition: form-data; name=\"_charset_\"\r\n\
+             \r\n\
+             UTF-8\r\n\
+             --boundary\r\n\
+             content-type: text/plain; charset=utf-8\r\n\
+             content-disposition: form-data; name=\"text\"\r\n\
+             \r\n\
+             Hello World!\r\n\
+             --boundary\r\n\
+             content-type: application/octet-stream\r\n\
+             content-disposition: form-data; name=\"input\"\r\n\
+             \r\n\
+             Hello World!\r\n\
+             --boundary--\r\n"
+        );
+    }
+
+    #[cfg(feature = "encoding")]
+    #[tokio::test]
+    async fn set_charset_transcodes_text() {
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.set_charset(Charset::for_label("windows-1252").unwrap());
+        form.add_text("text", "café");
+
+        let body = Body::from(form).try_concat().await.unwrap();
+
+        assert!(body
+            .windows(18)
+            .any(|window| window == b"charset=windows-12"));
+        assert!(body.windows(5).any(|window| window == b"caf\xe9\r"));
+    }
+
+    #[test]
+    fn form_parts_can_be_edited() {
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_text("a", "1");
+        form.add_reader("b", Cursor::new("2"));
+        form.add_text("a", "3");
+        form.add_file("c", test_file_path()).unwrap();
+        form.insert(1, Part::text("d", "4").with_filename("d.txt"));
+
+        let names: Vec<&str> = form.parts().map(Part::name).collect();
+
+        assert_eq!(names, vec!["a", "d", "b", "a", "c"]);
+        assert_eq!(form.len(), 5);
+        assert_eq!(form.parts().nth(1).unwrap().filename(), Some("d.txt"));
+
+        let removed = form.remove("a");
+
+        assert_eq!(removed.len(), 2);
+        assert!(!form.contains("a"));
+
+        form.retain(|part| part.content_type() != &mime::APPLICATION_OCTET_STREAM);
+
+        let names: Vec<&str> = form.parts().map(Part::name).collect();
+
+        assert_eq!(names, vec!["d", "c"]);
+        assert!(!form.is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_encoding_auto_writes_text_urlencoded() {
         let mut form = Form::default();
 
-        form.add_reader("input", bytes);
+        form.set_encoding(FormEncoding::Auto);
+        form.add_text("text", "Hello World!");
 
-        let data = form_output(form).await;
+        let req = form
+            .set_body::<Body>(Request::post("http://localhost/upload"))
+            .unwrap();
+        let content_type = req.headers().get(&CONTENT_TYPE).unwrap().clone();
+        let body = req.into_body().try_concat().await.unwrap();
 
-        assert!(data.contains("Hello World!"));
+        assert_eq!(content_type, "application/x-www-form-urlencoded");
+        assert_eq!(&body[..], b"text=Hello+World%21");
     }
 
     #[tokio::test]
-    async fn add_file_returns_expected_result() {
+    async fn set_encoding_urlencoded_fails_with_file_parts() {
         let mut form = Form::default();
 
-        assert!(form.add_file("test_file.txt", test_file_path()).is_ok());
+        form.set_encoding(FormEncoding::UrlEncoded);
+        form.add_text("text", "Hello World!");
+        form.add_file("file", test_file_path()).unwrap();
 
-        let data = form_output(form).await;
+        assert!(matches!(
+            Body::from(form).try_concat().await,
+            Err(Error::NotUrlEncodable { name }) if name == "file"
+        ));
+    }
 
-        assert!(data.contains("This is a test file!"));
-        assert!(data.contains("text/plain"));
+    #[test]
+    fn set_body_urlencoded_fails_with_parts_other_than_plain_text() {
+        let mut form = Form::default();
+
+        form.set_encoding(FormEncoding::UrlEncoded);
+        form.add_text("text", "Hello World!");
+        form.add_part(Part::text("json", "{}").with_mime(mime::APPLICATION_JSON));
+
+        assert!(matches!(
+            form.set_body::<Body>(Request::post("http://localhost/upload")),
+            Err(Error::NotUrlEncodable { name }) if name == "json"
+        ));
+
+        let mut form = Form::default();
+
+        form.set_encoding(FormEncoding::Auto);
+        form.add_part(Part::text("json", "{}").with_mime(mime::APPLICATION_JSON));
+
+        assert!(form.content_type().starts_with("multipart/form-data"));
     }
 
     #[tokio::test]
-    async fn add_file_with_mime_returns_expected_result() {
+    async fn abort_handle_stops_body_with_report() {
+        use futures_util::StreamExt;
+
         let mut form = Form::default();
 
-        assert!(form
-            .add_file_with_mime("test_file.txt", test_file_path(), mime::TEXT_CSV)
-            .is_ok());
+        form.add_text("files[]", "Hello World!");
+        form.add_reader("files[]", Cursor::new(vec![b'x'; 64 * 1024]));
 
-        let data = form_output(form).await;
+        let handle = form.abort_handle();
+        let mut body = Body::from(form);
 
-        assert!(data.contains("This is a test file!"));
-        assert!(data.contains("text/csv"));
+        // Write until some of the content of the second part has been sent.
+        while body.sent_parts.is_empty()
+            || body
+                .current
+                .as_ref()
+                .is_none_or(|current| current.offset == 0)
+        {
+            body.next().await.unwrap().unwrap();
+        }
+        handle.abort();
+
+        let report = match body.next().await {
+            Some(Err(Error::Aborted(report))) => report,
+            _ => panic!("expected the body to be aborted"),
+        };
+
+        let sent = &report.sent_parts()[0];
+        let interrupted = report.interrupted_part().unwrap();
+
+        assert_eq!(report.sent_parts().len(), 1);
+        assert_eq!(
+            (sent.index(), sent.name(), sent.offset()),
+            (0, "files[]", 12)
+        );
+        assert_eq!((interrupted.index(), interrupted.name()), (1, "files[]"));
+        assert!(report.interrupted_offset().unwrap() < 64 * 1024);
+        assert_eq!(report.bytes_written(), body.bytes_written());
+        assert!(body.next().await.is_none());
+        assert_eq!(handle.report(), Some(report));
+    }
+
+    #[tokio::test]
+    async fn abort_handle_reports_dropped_body() {
+        use futures_util::StreamExt;
+
+        let mut form = Form::default();
+
+        form.add_text("text", "Hello World!");
+
+        let handle = form.abort_handle();
+        let mut body = Body::from(form);
+
+        body.next().await.unwrap().unwrap();
+        drop(body);
+
+        let report = handle.report().unwrap();
+
+        assert!(report.sent_parts().is_empty());
+        assert_eq!(
+            report.interrupted_part().map(PartContext::name),
+            Some("text")
+        );
+        assert_eq!(report.interrupted_offset(), Some(0));
+
+        // A fully written body has nothing to report.
+        let mut form = Form::default();
+
+        form.add_text("text", "Hello World!");
+
+        let handle = form.abort_handle();
+
+        form_output(form).await;
+
+        assert!(handle.report().is_none());
     }
 
-    struct FixedBoundary;
-    impl crate::boundary::BoundaryGenerator for FixedBoundary {
-        fn generate_boundary() -> String {
-            "boundary".to_owned()
+    #[tokio::test]
+    async fn read_error_has_part_context() {
+        struct FailingRead;
+
+        impl std::io::Read for FailingRead {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read failed"))
+            }
         }
+
+        let mut form = Form::default();
+
+        form.add_text("text", "Hello World!");
+        form.add_reader_file("input", FailingRead, "input.bin");
+
+        let err = Body::from(form).try_concat().await.unwrap_err();
+        let part = err.part().unwrap();
+
+        assert!(matches!(err, Error::ContentRead { .. }));
+        assert_eq!(part.index(), 1);
+        assert_eq!(part.name(), "input");
+        assert_eq!(part.filename(), Some("input.bin"));
+        assert_eq!(part.offset(), 0);
+        assert_eq!(
+            err.to_string(),
+            "Failed to read multipart content in part 1 \"input\" \
+             (filename \"input.bin\") at byte 0: read failed"
+        );
     }
 
     #[tokio::test]
-    async fn test_form_body_stream() {
+    async fn body_stops_after_an_error() {
+        struct FailingRead;
+
+        impl std::io::Read for FailingRead {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read failed"))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.txt");
+
+        std::fs::write(&missing, "File").unwrap();
+
+        let mut forms = vec![];
+
         let mut form = Form::new::<FixedBoundary>();
-        // Text fields
-        form.add_text("name1", "value1");
-        form.add_text("name2", "value2");
+        form.add_text("text", "--boundary");
+        forms.push(("boundary_collision", form));
 
-        // Reader field
-        form.add_reader("input", Cursor::new("Hello World!"));
+        let mut form = Form::default();
+        form.add_text("a\r\nb", "Hello");
+        forms.push(("invalid_header", form));
 
-        let result: BytesMut = Body::from(form).try_concat().await.unwrap();
+        let mut form = Form::default();
+        form.set_max_part_len(3);
+        form.add_text("text", "Hello");
+        forms.push(("limit_exceeded", form));
+
+        let mut form = Form::default();
+        form.set_max_body_len(10);
+        form.add_text("text", "Hello");
+        forms.push(("limit_exceeded", form));
+
+        let mut form = Form::default();
+        form.add_reader("input", FailingRead);
+        forms.push(("content_read", form));
+
+        let mut form = Form::default();
+        form.add_file("file", &missing).unwrap();
+        forms.push(("file_open", form));
+
+        let mut form = Form::default();
+        form.set_encoding(FormEncoding::UrlEncoded);
+        form.add_reader("input", Cursor::new("Hello"));
+        forms.push(("not_url_encodable", form));
+
+        std::fs::remove_file(&missing).unwrap();
+
+        for (kind, mut form) in forms {
+            // Every error is followed by parts that would still be written.
+            form.add_text("after", "Hello");
 
+            let mut body = Body::from(form);
+
+            loop {
+                match body.try_next().await {
+                    Ok(Some(_)) => (),
+                    Ok(None) => panic!("expected a {} error", kind),
+                    Err(err) => {
+                        assert_eq!(err.kind(), kind);
+                        break;
+                    }
+                }
+            }
+
+            assert!(body.try_next().await.unwrap().is_none(), "{}", kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn boundary_in_content_is_an_error() {
+        /// Reads one byte at a time, so a delimiter is split across reads.
+        struct Trickle(Cursor<&'static str>);
+
+        impl std::io::Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(1);
+
+                self.0.read(&mut buf[..len])
+            }
+        }
+
+        let cases: [(&'static str, u64); 3] = [
+            ("--boundary", 0),
+            ("text\r\n--boundary--", 6),
+            ("\r\n--boundar", u64::MAX),
+        ];
+
+        for (content, offset) in cases {
+            let mut form = Form::new::<FixedBoundary>();
+
+            form.add_reader("input", Trickle(Cursor::new(content)));
+
+            match Body::from(form).try_concat().await {
+                Err(Error::BoundaryCollision { part }) => assert_eq!(part.offset(), offset),
+                Ok(_) => assert_eq!(offset, u64::MAX),
+                Err(e) => panic!("unexpected error {}", e),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn line_break_in_name_is_invalid_header() {
+        let mut form = Form::default();
+
+        form.add_text("text\r\nx-injected: 1", "Hello World!");
+
+        assert!(matches!(
+            Body::from(form).try_concat().await,
+            Err(Error::InvalidHeader { part, .. }) if part.index() == 0
+        ));
+    }
+
+    #[tokio::test]
+    async fn limits_are_enforced() {
+        let mut form = Form::default();
+
+        form.set_max_part_len(10);
+        form.add_text("short", "Hello");
+        form.add_text("long", "Hello World!");
+
+        match Body::from(form).try_concat().await {
+            Err(Error::LimitExceeded {
+                part: Some(part),
+                limit: Limit::PartLen(10),
+            }) => assert_eq!(part.name(), "long"),
+            _ => panic!("expected the part limit to be exceeded"),
+        }
+
+        let mut form = Form::default();
+
+        form.set_max_body_len(100);
+        form.add_text("text", "x".repeat(200));
+
+        assert!(matches!(
+            Body::from(form).try_concat().await,
+            Err(Error::LimitExceeded {
+                limit: Limit::BodyLen(100),
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn metrics_observe_body() {
+        use crate::metrics::tests::RecordingMetrics;
+        use std::sync::Arc;
+
+        let metrics = Arc::new(RecordingMetrics::default());
+        let mut form = Form::default();
+
+        form.set_metrics(metrics.clone());
+        form.set_max_part_len(10);
+        form.add_text("short", "Hello");
+        form.add_text("long", "Hello World!");
+
+        let mut body = Body::from(form);
+        let mut written = 0;
+
+        while let Some(Ok(chunk)) = body.try_next().await.transpose() {
+            written += chunk.len() as u64;
+        }
+
+        assert_eq!(*metrics.bytes.lock().unwrap(), written);
         assert_eq!(
-            result.as_ref(),
+            *metrics.events.lock().unwrap(),
             [
-                b"--boundary\r\n".as_ref(),
-                b"content-type: text/plain\r\n".as_ref(),
-                b"content-disposition: form-data; name=\"name1\"\r\n".as_ref(),
-                b"\r\n".as_ref(),
-                b"value1\r\n".as_ref(),
-                b"--boundary\r\n".as_ref(),
-                b"content-type: text/plain\r\n".as_ref(),
-                b"content-disposition: form-data; name=\"name2\"\r\n".as_ref(),
-                b"\r\n".as_ref(),
-                b"value2\r\n".as_ref(),
-                b"--boundary\r\n".as_ref(),
-                b"content-type: application/octet-stream\r\n".as_ref(),
-                b"content-disposition: form-data; name=\"input\"\r\n".as_ref(),
-                b"\r\n".as_ref(),
-                b"Hello World!\r\n".as_ref(),
-                b"--boundary--\r\n".as_ref(),
+                "part_written short 5",
+                "reader_pending short",
+                "write_error limit_exceeded",
             ]
-            .into_iter()
-            .flatten()
-            .copied()
-            .collect::<Vec<u8>>()
         );
     }
 
+    /// A reader that never produces anything.
+    struct Hang;
+
+    impl futures_util::io::AsyncRead for Hang {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            _: &mut [u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            std::task::Poll::Pending
+        }
+    }
+
     #[tokio::test]
-    async fn test_content_type_header_format() {
-        use http::Request;
+    async fn idle_timeout_fails_hung_reader() {
+        use crate::{error::Timeout, throttle::tests::ManualTimer, timeout::Timeouts};
+        use std::time::Duration;
+
+        let mut form = Form::default();
+
+        form.add_text("text", "Hello");
+        form.add_async_reader("hung", Hang);
+        form.set_timeouts(Timeouts::new(ManualTimer::new()).with_idle(Duration::from_secs(1)));
+
+        match Body::from(form).try_concat().await {
+            Err(Error::Timeout {
+                part: Some(part),
+                timeout: Timeout::Idle(idle),
+            }) => {
+                assert_eq!(part.name(), "hung");
+                assert_eq!(idle, Duration::from_secs(1));
+            }
+            _ => panic!("expected the idle timeout to pass"),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn total_timeout_fails_body() {
+        use crate::{error::Timeout, throttle::TokioTimer, timeout::Timeouts};
+        use std::time::Duration;
+
+        let mut form = Form::default();
+
+        form.add_async_reader("hung", Hang);
+        form.set_timeouts(Timeouts::new(TokioTimer).with_total(Duration::from_millis(10)));
+
+        let mut body = Body::from(form);
+
+        assert!(body.try_next().await.unwrap().is_some());
+        assert!(matches!(
+            body.try_next().await,
+            Err(Error::Timeout {
+                part: Some(_),
+                timeout: Timeout::Total(_),
+            })
+        ));
+        assert!(body.try_next().await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[tokio::test]
+    async fn mapped_file_matches_read_file() {
+        let read = {
+            let mut form = Form::new::<FixedBoundary>();
+
+            form.add_file("input", test_file_path()).unwrap();
+            form_output(form).await
+        };
+        let mapped = {
+            let mut form = Form::new::<FixedBoundary>();
+
+            // SAFETY: Nothing else writes the test file.
+            unsafe { form.map_files(0) };
+            form.add_file("input", test_file_path()).unwrap();
+            form_output(form).await
+        };
+
+        assert_eq!(mapped, read);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[tokio::test]
+    async fn mapped_file_truncation_is_an_error() {
+        use std::{fs::OpenOptions, future::poll_fn};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.bin");
+
+        std::fs::write(&path, vec![b'x'; 3 * 1024 * 1024]).unwrap();
+
+        let mut form = Form::default();
+
+        // SAFETY: The file is only truncated after its chunk is dropped, and
+        // before the next one is checked.
+        unsafe { form.map_files(1024) };
+        form.add_file("input", &path).unwrap();
+
+        let mut body = Body::from(form);
+
+        // The headers, and then the first slice of the mapping.
+        poll_fn(|cx| body.poll_bytes(cx)).await.unwrap().unwrap();
+        let chunk = poll_fn(|cx| body.poll_bytes(cx)).await.unwrap().unwrap();
+
+        assert_eq!(chunk.len(), 1024 * 1024);
+        drop(chunk);
+
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(10)
+            .unwrap();
+
+        match poll_fn(|cx| body.poll_bytes(cx)).await {
+            Some(Err(Error::FileChanged {
+                part: Some(part),
+                len,
+                actual,
+                ..
+            })) => {
+                assert_eq!(part.name(), "input");
+                assert_eq!(part.offset(), 1024 * 1024);
+                assert_eq!((len, actual), (3 * 1024 * 1024, 10));
+            }
+            _ => panic!("expected the file to have changed"),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_rate_limiter_delays_body() {
+        use crate::throttle::{tests::ManualTimer, RateLimiter};
+
+        let timer = ManualTimer::new();
+        let start = timer.now.lock().unwrap().to_owned();
+        let text = "x".repeat(1000);
 
         let mut form = Form::new::<FixedBoundary>();
-        // Text fields
-        form.add_text("name1", "value1");
-        form.add_text("name2", "value2");
+        form.add_text("text", &text);
+        let expected = form_output(form).await;
 
-        let builder = Request::builder();
-        let body = form.set_body::<Body>(builder).unwrap();
+        let mut form = Form::new::<FixedBoundary>();
+        form.add_text("text", &text);
+        form.set_rate_limiter(RateLimiter::new(100, 100, timer.clone()));
 
+        assert_eq!(form_output(form).await, expected);
+        // Writing the last chunk waits for the debt of the text to be paid.
+        assert!(timer.elapsed(start) >= std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn known_length_accounts_for_encoding() {
+        let text = Part::text("a", "Hello World!");
+        let encoded =
+            Part::text("a", "x".repeat(100)).with_transfer_encoding(TransferEncoding::Base64);
+        let qp = Part::text("a", "Hello").with_transfer_encoding(TransferEncoding::QuotedPrintable);
+        let file = Part::file("a", test_file_path()).unwrap();
+        let reader = Part::reader("a", Cursor::new("Hello"));
+
+        assert_eq!(text.known_length(), Some(12));
+        // Two lines: 76 characters, a CRLF, and 60 characters.
+        assert_eq!(encoded.known_length(), Some(138));
+        assert_eq!(qp.known_length(), None);
         assert_eq!(
-            body.headers().get("Content-Type").unwrap().as_bytes(),
-            b"multipart/form-data; boundary=boundary",
-        )
+            file.known_length(),
+            Some(test_file_path().metadata().unwrap().len())
+        );
+        assert_eq!(reader.known_length(), None);
+    }
+
+    #[tokio::test]
+    async fn set_subtype_writes_parts_without_disposition() {
+        use http::header::{HeaderName, HeaderValue};
+
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.set_subtype("mixed");
+        form.add_part(Part::text("ignored", "Hello World!").with_header(
+            HeaderName::from_static("content-id"),
+            HeaderValue::from_static("<1>"),
+        ));
+
+        assert_eq!(form.content_type(), "multipart/mixed; boundary=boundary");
+        assert_eq!(
+            form_output(form).await,
+            "--boundary\r\n\
+             content-type: text/plain\r\n\
+             content-id: <1>\r\n\
+             \r\n\
+             Hello World!\r\n\
+             --boundary--\r\n"
+        );
     }
 }
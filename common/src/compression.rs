@@ -0,0 +1,288 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+#[cfg(feature = "__compression")]
+use crate::encoding::Encode;
+use crate::error::Error;
+#[cfg(feature = "__compression")]
+use crate::error::Limit;
+#[cfg(feature = "__compression")]
+use bytes::{BufMut, BytesMut};
+#[cfg(feature = "__compression")]
+use std::io::{self, Read, Write};
+
+/// The compression algorithms that can be applied to the content of a part
+/// while it is written. Each algorithm is enabled by the feature of the same
+/// name.
+///
+/// The algorithm is sent in the part's Content-Encoding header, which the
+/// receiving server has to understand.
+///
+/// [See RFC9110 8.4](https://tools.ietf.org/html/rfc9110#section-8.4).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// The gzip format.
+    #[cfg(feature = "gzip")]
+    Gzip,
+
+    /// The zlib format, which is what the `deflate` content coding means.
+    #[cfg(feature = "deflate")]
+    Deflate,
+
+    /// The brotli format.
+    #[cfg(feature = "brotli")]
+    Brotli,
+
+    /// The zstd format.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the value of the Content-Encoding header.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gzip",
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => "deflate",
+            #[cfg(feature = "brotli")]
+            Compression::Brotli => "br",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Parses the value of a Content-Encoding header. Returns `None` for
+    /// `identity`, and encodings that aren't enabled.
+    pub fn from_name(name: &str) -> Option<Compression> {
+        let name = name.trim();
+
+        #[cfg(feature = "gzip")]
+        if name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip") {
+            return Some(Compression::Gzip);
+        }
+
+        #[cfg(feature = "deflate")]
+        if name.eq_ignore_ascii_case("deflate") {
+            return Some(Compression::Deflate);
+        }
+
+        #[cfg(feature = "brotli")]
+        if name.eq_ignore_ascii_case("br") {
+            return Some(Compression::Brotli);
+        }
+
+        #[cfg(feature = "zstd")]
+        if name.eq_ignore_ascii_case("zstd") {
+            return Some(Compression::Zstd);
+        }
+
+        let _ = name;
+
+        None
+    }
+
+    /// Decompresses the content of a part that was received with this
+    /// encoding. Fails with `Error::LimitExceeded` if the content
+    /// decompresses to more than `max_len` bytes.
+    pub fn decode(&self, input: &[u8], max_len: u64) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "__compression")]
+        {
+            let read: Box<dyn Read + '_> = match *self {
+                #[cfg(feature = "gzip")]
+                Compression::Gzip => Box::new(flate2::read::GzDecoder::new(input)),
+                #[cfg(feature = "deflate")]
+                Compression::Deflate => Box::new(flate2::read::ZlibDecoder::new(input)),
+                #[cfg(feature = "brotli")]
+                Compression::Brotli => Box::new(brotli::Decompressor::new(input, 4096)),
+                #[cfg(feature = "zstd")]
+                Compression::Zstd => Box::new(
+                    zstd::stream::read::Decoder::new(input)
+                        .map_err(|_| Error::Decode(self.as_str()))?,
+                ),
+            };
+            let mut out = Vec::new();
+
+            // One byte past the limit is enough to tell it was exceeded.
+            read.take(max_len.saturating_add(1))
+                .read_to_end(&mut out)
+                .map_err(|_| Error::Decode(self.as_str()))?;
+
+            if out.len() as u64 > max_len {
+                return Err(Error::LimitExceeded {
+                    part: None,
+                    limit: Limit::PartLen(max_len),
+                });
+            }
+
+            Ok(out)
+        }
+
+        #[cfg(not(feature = "__compression"))]
+        {
+            let _ = (input, max_len);
+
+            match *self {}
+        }
+    }
+
+    #[cfg(feature = "__compression")]
+    pub(crate) fn encoder(&self) -> io::Result<Box<dyn Encode>> {
+        let writer: Box<dyn CompressWrite> = match *self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Box::new(flate2::write::GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => Box::new(flate2::write::ZlibEncoder::new(
+                Vec::new(),
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "brotli")]
+            Compression::Brotli => Box::new(brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(zstd::stream::write::Encoder::new(Vec::new(), 0)?),
+        };
+
+        Ok(Box::new(Compressor {
+            writer: Some(writer),
+        }))
+    }
+}
+
+/// A compressor that writes to an in memory buffer.
+#[cfg(feature = "__compression")]
+trait CompressWrite: Write + Send {
+    /// Returns the compressed data written so far.
+    fn output(&mut self) -> &mut Vec<u8>;
+
+    /// Writes the end of the compressed stream, and returns the rest of the
+    /// compressed data.
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>>;
+}
+
+#[cfg(feature = "gzip")]
+impl CompressWrite for flate2::write::GzEncoder<Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        flate2::write::GzEncoder::finish(*self)
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl CompressWrite for flate2::write::ZlibEncoder<Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        flate2::write::ZlibEncoder::finish(*self)
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl CompressWrite for brotli::CompressorWriter<Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        Ok(self.into_inner())
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl CompressWrite for zstd::stream::write::Encoder<'static, Vec<u8>> {
+    fn output(&mut self) -> &mut Vec<u8> {
+        self.get_mut()
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<Vec<u8>> {
+        zstd::stream::write::Encoder::finish(*self)
+    }
+}
+
+#[cfg(feature = "__compression")]
+struct Compressor {
+    /// The compressor, until the stream is finished.
+    writer: Option<Box<dyn CompressWrite>>,
+}
+
+#[cfg(feature = "__compression")]
+impl Encode for Compressor {
+    fn encode(&mut self, input: &[u8], out: &mut BytesMut) -> io::Result<()> {
+        if let Some(ref mut writer) = self.writer {
+            writer.write_all(input)?;
+
+            let output = writer.output();
+
+            out.put_slice(output);
+            output.clear();
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut BytesMut) -> io::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            out.put_slice(&writer.finish()?);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use super::Compression;
+    use crate::encoding::EncodedRead;
+    use futures_util::io::{AsyncReadExt, Cursor};
+
+    #[tokio::test]
+    async fn gzip_round_trip() {
+        let input = "Hello World!".repeat(1024);
+        let read = Box::new(Cursor::new(input.clone()));
+        let mut compressed = EncodedRead::new(read, Compression::Gzip.encoder().unwrap());
+        let mut out = vec![];
+
+        compressed.read_to_end(&mut out).await.unwrap();
+
+        assert!(out.len() < input.len());
+        assert_eq!(
+            Compression::Gzip.decode(&out, u64::MAX).unwrap(),
+            input.as_bytes().to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn decode_fails_past_max_len() {
+        use crate::error::{Error, Limit};
+
+        let input = vec![0; 1 << 20];
+        let read = Box::new(Cursor::new(input.clone()));
+        let mut compressed = EncodedRead::new(read, Compression::Gzip.encoder().unwrap());
+        let mut out = vec![];
+
+        compressed.read_to_end(&mut out).await.unwrap();
+
+        assert_eq!(Compression::Gzip.decode(&out, 1 << 20).unwrap(), input);
+        assert!(matches!(
+            Compression::Gzip.decode(&out, 4096),
+            Err(Error::LimitExceeded {
+                part: None,
+                limit: Limit::PartLen(4096),
+            })
+        ));
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Conversion between parts, and curl's `-F` and `--form-string` arguments.
+//!
+//! [See the curl manual](https://curl.se/docs/manpage.html#-F).
+
+use crate::{
+    client_::{Part, Source},
+    encoding::TransferEncoding,
+    error::Error,
+};
+use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use mime::Mime;
+use std::{io::Cursor, path::Path};
+
+/// Writes the arguments that make curl send a part.
+pub(crate) fn write_args(part: &Part<'_>, out: &mut Vec<String>) {
+    let mut params = String::new();
+
+    // curl only takes the essence of a type, a type with parameters is sent
+    // as a header instead, which replaces the one curl would write.
+    let content_type = part.content_type();
+    let default_type = match part.source() {
+        Source::Text(_) => content_type == &mime::TEXT_PLAIN,
+        _ => false,
+    };
+    if !default_type {
+        if content_type.params().next().is_none() {
+            params.push_str(";type=");
+            params.push_str(content_type.essence_str());
+        } else {
+            push_param(
+                &mut params,
+                "headers",
+                &format!("Content-Type: {}", content_type),
+            );
+        }
+    }
+    if let Some(filename) = part.filename() {
+        push_param(&mut params, "filename", filename);
+    }
+    if let Some(encoding) = part.transfer_encoding() {
+        params.push_str(";encoder=");
+        params.push_str(encoding.as_str());
+    }
+    for (name, value) in part.headers().iter() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+
+        push_param(&mut params, "headers", &format!("{}: {}", name, value));
+    }
+
+    match part.source() {
+        // Text without options is passed literally, so it doesn't have to be
+        // quoted.
+        Source::Text(text) if params.is_empty() && text_needs_quotes(text) => {
+            out.push("--form-string".to_string());
+            out.push(format!("{}={}", part.name(), text));
+        }
+        Source::Text(text) => {
+            let text = if text_needs_quotes(text) {
+                quote(text)
+            } else {
+                text.to_string()
+            };
+
+            out.push("-F".to_string());
+            out.push(format!("{}={}{}", part.name(), text, params));
+        }
+        Source::File(path) => {
+            out.push("-F".to_string());
+            out.push(format!(
+                "{}=@{}{}",
+                part.name(),
+                word(&path.to_string_lossy()),
+                params
+            ));
+        }
+        Source::Stream => {
+            out.push("-F".to_string());
+            out.push(format!("{}=@-{}", part.name(), params));
+        }
+    }
+}
+
+fn push_param(params: &mut String, name: &str, value: &str) {
+    params.push(';');
+    params.push_str(name);
+    params.push('=');
+    params.push_str(&word(value));
+}
+
+/// Returns true if curl would not read a value as is.
+fn needs_quotes(value: &str) -> bool {
+    value.starts_with('"')
+        || value.contains([';', ',', '"'])
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+}
+
+/// Returns true if curl would not read text content as is, which is also
+/// the case if it would read it as a file.
+fn text_needs_quotes(text: &str) -> bool {
+    text.starts_with(['@', '<']) || needs_quotes(text)
+}
+
+/// Quotes a value if it needs to be.
+fn word(value: &str) -> String {
+    if needs_quotes(value) {
+        quote(value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Parses curl arguments into parts. Arguments other than `-F`, `--form`, and
+/// `--form-string` are skipped.
+pub(crate) fn parse_args<'a, I, S>(args: I) -> Result<Vec<Part<'a>>, Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter();
+    let mut parts = vec![];
+
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        let literal = match arg {
+            "-F" | "--form" => false,
+            "--form-string" => true,
+            _ => match arg.strip_prefix("-F") {
+                Some(value) if !value.is_empty() => {
+                    parts.push(parse_form(value)?);
+                    continue;
+                }
+                _ => continue,
+            },
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| Error::Parse(format!("missing value for {}", arg)))?;
+
+        parts.push(if literal {
+            parse_form_string(value.as_ref())?
+        } else {
+            parse_form(value.as_ref())?
+        });
+    }
+
+    Ok(parts)
+}
+
+fn split_name(value: &str) -> Result<(&str, &str), Error> {
+    value
+        .split_once('=')
+        .ok_or_else(|| Error::Parse(format!("missing '=' in form argument {:?}", value)))
+}
+
+fn parse_form_string<'a>(value: &str) -> Result<Part<'a>, Error> {
+    let (name, text) = split_name(value)?;
+
+    Ok(Part::text(name, text))
+}
+
+fn parse_form<'a>(value: &str) -> Result<Part<'a>, Error> {
+    let (name, content) = split_name(value)?;
+
+    let (mut part, mut rest) = if let Some(path) = content.strip_prefix('@') {
+        let (path, rest) = read_word(path, &[';', ',']);
+
+        if rest.starts_with(',') {
+            return Err(Error::Parse(
+                "multiple files in one form argument are not supported".to_string(),
+            ));
+        }
+        if path == "-" {
+            return Err(Error::Parse(
+                "content from standard input can't be read".to_string(),
+            ));
+        }
+
+        // curl sends the name of the file, without its directory.
+        let filename = Path::new(&path)
+            .file_name()
+            .map(|filename| filename.to_string_lossy().into_owned());
+        let part = Part::file(name, &path).map_err(|source| Error::FileOpen {
+            part: None,
+            path: path.clone().into(),
+            source,
+        })?;
+
+        let part = match filename {
+            Some(filename) => part.with_filename(filename),
+            None => part,
+        };
+
+        (part, rest)
+    } else if let Some(path) = content.strip_prefix('<') {
+        let (path, rest) = read_word(path, &[';']);
+        let content = std::fs::read(&path).map_err(|source| Error::FileOpen {
+            part: None,
+            path: path.clone().into(),
+            source,
+        })?;
+
+        let part = match String::from_utf8(content) {
+            Ok(text) => Part::text(name, text),
+            Err(e) => Part::reader(name, Cursor::new(e.into_bytes())).with_mime(mime::TEXT_PLAIN),
+        };
+
+        (part, rest)
+    } else {
+        let (text, rest) = read_word(content, &[';']);
+
+        (Part::text(name, text), rest)
+    };
+
+    while let Some(param) = rest.strip_prefix(';') {
+        let param = param.trim_start();
+        let (key, value) = param
+            .split_once('=')
+            .ok_or_else(|| Error::Parse(format!("invalid form option {:?}", param)))?;
+        let (value, next) = read_word(value, &[';']);
+
+        part = match key {
+            "type" => part.with_mime(parse_mime(&value)?),
+            "filename" => part.with_filename(value),
+            "encoder" => match TransferEncoding::from_name(&value) {
+                Some(encoding) => part.with_transfer_encoding(encoding),
+                None => part,
+            },
+            "headers" => {
+                let (name, value) = value
+                    .split_once(':')
+                    .ok_or_else(|| Error::Parse(format!("invalid form header {:?}", value)))?;
+                let name = HeaderName::from_bytes(name.trim().as_bytes())
+                    .map_err(|_| Error::Parse(format!("invalid form header name {:?}", name)))?;
+
+                if name == CONTENT_TYPE {
+                    part.with_mime(parse_mime(value.trim())?)
+                } else {
+                    let value = HeaderValue::from_str(value.trim()).map_err(|_| {
+                        Error::Parse(format!("invalid form header value {:?}", value))
+                    })?;
+
+                    part.with_header(name, value)
+                }
+            }
+            _ => return Err(Error::Parse(format!("unknown form option {:?}", key))),
+        };
+        rest = next;
+    }
+
+    Ok(part)
+}
+
+fn parse_mime(value: &str) -> Result<Mime, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Parse(format!("invalid form type {:?}", value)))
+}
+
+/// Reads a value that may be quoted, like curl does. Returns the value, and
+/// the rest of the input, starting at the separator.
+fn read_word<'s>(input: &'s str, separators: &[char]) -> (String, &'s str) {
+    if let Some(quoted) = input.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = quoted.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' if quoted[i + 1..].starts_with(['"', '\\']) => {
+                    if let Some((_, c)) = chars.next() {
+                        value.push(c);
+                    }
+                }
+                '"' => {
+                    // Anything between the closing quote, and the separator
+                    // is ignored.
+                    let rest = &quoted[i + 1..];
+                    let end = rest.find(separators).unwrap_or(rest.len());
+
+                    return (value, &rest[end..]);
+                }
+                c => value.push(c),
+            }
+        }
+        // An unterminated quote is read as part of the value.
+    }
+
+    let end = input.find(separators).unwrap_or(input.len());
+
+    (input[..end].trim_end().to_string(), &input[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, read_word, write_args};
+    use crate::client_::Part;
+
+    fn args(part: &Part<'_>) -> Vec<String> {
+        let mut out = vec![];
+
+        write_args(part, &mut out);
+
+        out
+    }
+
+    #[test]
+    fn read_word_unquotes_values() {
+        assert_eq!(
+            read_word("a b ;type=x", &[';']),
+            ("a b".to_string(), ";type=x")
+        );
+        assert_eq!(
+            read_word(r#""a;\"b\"\\c" x;y"#, &[';']),
+            ("a;\"b\"\\c".to_string(), ";y")
+        );
+        assert_eq!(read_word("\"a;b", &[';']), ("\"a".to_string(), ";b"));
+    }
+
+    #[test]
+    fn write_args_quotes_special_values() {
+        assert_eq!(args(&Part::text("a", "Hello")), ["-F", "a=Hello"]);
+        assert_eq!(
+            args(&Part::text("a", "@not a file")),
+            ["--form-string", "a=@not a file"]
+        );
+        assert_eq!(
+            args(&Part::text("a", "x;y").with_filename("a \"b\".txt")),
+            ["-F", r#"a="x;y";filename="a \"b\".txt""#]
+        );
+        assert_eq!(
+            args(&Part::text("a", "{}").with_mime(mime::APPLICATION_JSON)),
+            ["-F", "a={};type=application/json"]
+        );
+        assert_eq!(
+            args(&Part::text("a", "x").with_mime(mime::TEXT_PLAIN_UTF_8)),
+            [
+                "-F",
+                "a=x;headers=\"Content-Type: text/plain; charset=utf-8\""
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_args_reads_parts() {
+        let parts = parse_args([
+            "https://example.com",
+            "-H",
+            "X-Trace: 1",
+            "-F",
+            "text=Hello;type=text/html;filename=\"a;b.html\"",
+            "--form-string",
+            "literal=@x;type=y",
+            "-Fheader=x;headers=\"X-Trace: 1\"",
+        ])
+        .unwrap();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].name(), "text");
+        assert_eq!(parts[0].content_type(), &mime::TEXT_HTML);
+        assert_eq!(parts[0].filename(), Some("a;b.html"));
+        assert_eq!(args(&parts[1]), ["--form-string", "literal=@x;type=y"]);
+        assert_eq!(args(&parts[2]), ["-F", "header=x;headers=x-trace: 1"]);
+    }
+
+    #[test]
+    fn parse_args_rejects_invalid_arguments() {
+        assert!(parse_args(["-F"]).is_err());
+        assert!(parse_args(["-F", "novalue"]).is_err());
+        assert!(parse_args(["-F", "a=b;unknown=c"]).is_err());
+        assert!(parse_args(["-F", "a=@-"]).is_err());
+        assert!(parse_args(["-F", "a=@missing-file"]).is_err());
+    }
+
+    #[test]
+    fn file_args_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data; 1.csv");
+
+        std::fs::write(&path, "a,b").unwrap();
+
+        let part = Part::file("upload", &path)
+            .unwrap()
+            .with_filename("report.csv");
+        let rendered = args(&part);
+        let parts = parse_args(&rendered).unwrap();
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].name(), "upload");
+        assert_eq!(parts[0].filename(), Some("report.csv"));
+        assert_eq!(parts[0].content_type(), &mime::TEXT_CSV);
+        assert_eq!(args(&parts[0]), rendered);
+    }
+}
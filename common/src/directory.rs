@@ -0,0 +1,208 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use glob::Pattern;
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// Determines how symbolic links are handled when walking a directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Symbolic links are ignored.
+    #[default]
+    Skip,
+
+    /// Symbolic links are followed, and the files (or directories) they
+    /// point to are uploaded as if they were located at the link.
+    Follow,
+}
+
+/// Options that control which files [`Form::add_directory`] adds to a form.
+///
+/// Include and exclude patterns are [glob](https://docs.rs/glob) patterns
+/// matched against the path of each entry relative to the directory, using
+/// `/` as a separator. A file is added if it matches any include pattern (or
+/// no include patterns are set), and does not match any exclude pattern.
+/// Directories matching an exclude pattern are skipped entirely.
+///
+/// [`Form::add_directory`]: crate::client::multipart::Form::add_directory
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::{DirectoryOptions, SymlinkPolicy};
+///
+/// let options = DirectoryOptions::new()
+///     .include("**/*.rs")
+///     .exclude("target")
+///     .symlinks(SymlinkPolicy::Follow);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DirectoryOptions {
+    recursive: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    symlinks: SymlinkPolicy,
+}
+
+impl Default for DirectoryOptions {
+    #[inline]
+    fn default() -> DirectoryOptions {
+        DirectoryOptions {
+            recursive: true,
+            include: vec![],
+            exclude: vec![],
+            symlinks: SymlinkPolicy::default(),
+        }
+    }
+}
+
+impl DirectoryOptions {
+    /// Creates the default options, which recursively add every file, and
+    /// skip symbolic links.
+    #[inline]
+    pub fn new() -> DirectoryOptions {
+        DirectoryOptions::default()
+    }
+
+    /// Sets whether files in subdirectories are added.
+    pub fn recursive(mut self, recursive: bool) -> DirectoryOptions {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Adds a pattern that files must match to be added.
+    pub fn include<P>(mut self, pattern: P) -> DirectoryOptions
+    where
+        P: Into<String>,
+    {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Adds a pattern for files and directories that should not be added.
+    pub fn exclude<P>(mut self, pattern: P) -> DirectoryOptions
+    where
+        P: Into<String>,
+    {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Sets how symbolic links are handled.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> DirectoryOptions {
+        self.symlinks = policy;
+        self
+    }
+}
+
+/// A file found while walking a directory.
+pub(crate) struct DirectoryEntry {
+    /// The path used to open the file.
+    pub path: PathBuf,
+
+    /// The filename sent with the part. This is the path relative to the
+    /// parent of the directory, the same way browsers name files selected
+    /// with `webkitdirectory`.
+    pub filename: String,
+}
+
+fn compile(patterns: &[String]) -> io::Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        })
+        .collect()
+}
+
+/// Joins the components of a relative path with `/`, regardless of the
+/// platform's separator.
+fn to_slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(s) => Some(s.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lists the files in a directory that should be added to a form, sorted by
+/// path so the order of parts is stable.
+pub(crate) fn walk(root: &Path, options: &DirectoryOptions) -> io::Result<Vec<DirectoryEntry>> {
+    let include = compile(&options.include)?;
+    let exclude = compile(&options.exclude)?;
+
+    if !root.metadata()?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "expected a directory not file",
+        ));
+    }
+
+    // `.` and `..` don't have a file name, so the canonical path is used to
+    // find the name of the directory.
+    let root_name = match root.file_name() {
+        Some(name) => Some(name.to_string_lossy().into_owned()),
+        None => root
+            .canonicalize()?
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+    };
+
+    let mut walker = WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(options.symlinks == SymlinkPolicy::Follow)
+        .sort_by_file_name();
+
+    if !options.recursive {
+        walker = walker.max_depth(1);
+    }
+
+    let relative = |path: &Path| to_slash_path(path.strip_prefix(root).unwrap_or(path));
+
+    let entries = walker.into_iter().filter_entry(|entry| {
+        let path = relative(entry.path());
+
+        !exclude.iter().any(|pattern| pattern.matches(&path))
+    });
+
+    let mut files = vec![];
+
+    for entry in entries {
+        let entry = entry?;
+
+        // Skips directories, and symbolic links if they aren't followed. When
+        // links are followed, the file type is the type of the target.
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = relative(entry.path());
+
+        if !include.is_empty() && !include.iter().any(|pattern| pattern.matches(&path)) {
+            continue;
+        }
+
+        let filename = match root_name {
+            Some(ref root_name) => format!("{}/{}", root_name, path),
+            None => path,
+        };
+
+        files.push(DirectoryEntry {
+            path: entry.into_path(),
+            filename,
+        });
+    }
+
+    Ok(files)
+}
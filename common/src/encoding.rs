@@ -0,0 +1,478 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::error::Error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::{Buf, BufMut, BytesMut};
+use futures_core::ready;
+use futures_util::io::AsyncRead;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The maximum length of an encoded line, not including the CRLF.
+///
+/// [See RFC2045 6.7](https://tools.ietf.org/html/rfc2045#section-6.7).
+const MAX_LINE_LEN: usize = 76;
+
+/// The number of input bytes encoded on each base64 line.
+const BASE64_LINE_INPUT_LEN: usize = MAX_LINE_LEN / 4 * 3;
+
+/// The encodings that can be applied to the content of a part, so that it
+/// only contains 7-bit data.
+///
+/// RFC 7578 deprecates the Content-Transfer-Encoding header for HTTP, but
+/// some servers and mail gateways still require it.
+///
+/// [See RFC2045 6](https://tools.ietf.org/html/rfc2045#section-6) and
+/// [RFC7578 4.7](https://tools.ietf.org/html/rfc7578#section-4.7).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransferEncoding {
+    /// Base64, with lines wrapped at 76 characters.
+    Base64,
+
+    /// Quoted-printable. CRLF sequences are kept as line breaks, so text
+    /// stays readable.
+    QuotedPrintable,
+}
+
+impl TransferEncoding {
+    /// Returns the value of the Content-Transfer-Encoding header.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TransferEncoding::Base64 => "base64",
+            TransferEncoding::QuotedPrintable => "quoted-printable",
+        }
+    }
+
+    /// Parses the value of a Content-Transfer-Encoding header. Returns `None`
+    /// for identity encodings like `binary`, and unknown encodings.
+    pub fn from_name(name: &str) -> Option<TransferEncoding> {
+        let name = name.trim();
+
+        if name.eq_ignore_ascii_case("base64") {
+            Some(TransferEncoding::Base64)
+        } else if name.eq_ignore_ascii_case("quoted-printable") {
+            Some(TransferEncoding::QuotedPrintable)
+        } else {
+            None
+        }
+    }
+
+    /// Decodes the content of a part that was received with this encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use common_multipart_rfc7578::client::multipart::TransferEncoding;
+    ///
+    /// let decoded = TransferEncoding::QuotedPrintable
+    ///     .decode(b"caf=C3=A9 au lait")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(decoded, "café au lait".as_bytes());
+    /// ```
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        match *self {
+            TransferEncoding::Base64 => decode_base64(input),
+            TransferEncoding::QuotedPrintable => Ok(decode_quoted_printable(input)),
+        }
+    }
+
+    /// Returns the length of content of the given length once it is
+    /// encoded, if it doesn't depend on the content.
+    pub(crate) fn encoded_len(&self, len: u64) -> Option<u64> {
+        let line_len = BASE64_LINE_INPUT_LEN as u64;
+
+        match *self {
+            TransferEncoding::Base64 if len == 0 => Some(0),
+            TransferEncoding::Base64 => {
+                Some(len.div_ceil(3) * 4 + (len.div_ceil(line_len) - 1) * 2)
+            }
+            TransferEncoding::QuotedPrintable => None,
+        }
+    }
+
+    pub(crate) fn encoder(&self) -> Box<dyn Encode> {
+        match *self {
+            TransferEncoding::Base64 => Box::new(Base64Encoder {
+                pending: Vec::with_capacity(BASE64_LINE_INPUT_LEN),
+                first_line: true,
+            }),
+            TransferEncoding::QuotedPrintable => Box::new(QuotedPrintableEncoder {
+                line_len: 0,
+                pending_space: None,
+                pending_cr: false,
+            }),
+        }
+    }
+}
+
+/// Transforms the content of a part while it is streamed.
+pub(crate) trait Encode: Send {
+    /// Encodes a chunk of the content.
+    fn encode(&mut self, input: &[u8], out: &mut BytesMut) -> io::Result<()>;
+
+    /// Writes anything left over once all of the content is encoded.
+    fn finish(&mut self, out: &mut BytesMut) -> io::Result<()>;
+}
+
+/// A reader that encodes the content of another reader.
+pub(crate) struct EncodedRead<'a> {
+    read: Box<dyn 'a + AsyncRead + Send + Unpin>,
+
+    encoder: Box<dyn Encode>,
+
+    /// The buffer the underlying reader is read into.
+    input: Box<[u8]>,
+
+    /// Encoded data that hasn't been read yet.
+    output: BytesMut,
+
+    /// Whether the underlying reader is exhausted.
+    finished: bool,
+}
+
+impl<'a> EncodedRead<'a> {
+    pub(crate) fn new(
+        read: Box<dyn 'a + AsyncRead + Send + Unpin>,
+        encoder: Box<dyn Encode>,
+    ) -> EncodedRead<'a> {
+        EncodedRead {
+            read,
+            encoder,
+            input: vec![0; 8 * 1024].into_boxed_slice(),
+            output: BytesMut::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<'a> AsyncRead for EncodedRead<'a> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Some encoders buffer their input, so this may take a few reads
+        // before there is any output.
+        while this.output.is_empty() {
+            if this.finished {
+                return Poll::Ready(Ok(0));
+            }
+
+            match ready!(Pin::new(&mut this.read).poll_read(cx, &mut this.input))? {
+                0 => {
+                    this.finished = true;
+                    this.encoder.finish(&mut this.output)?;
+                }
+                len => this.encoder.encode(&this.input[..len], &mut this.output)?,
+            }
+        }
+
+        let len = buf.len().min(this.output.len());
+
+        buf[..len].copy_from_slice(&this.output[..len]);
+        this.output.advance(len);
+
+        Poll::Ready(Ok(len))
+    }
+}
+
+struct Base64Encoder {
+    /// Input that doesn't fill a line yet.
+    pending: Vec<u8>,
+
+    first_line: bool,
+}
+
+impl Base64Encoder {
+    fn write_line(&mut self, input: &[u8], out: &mut BytesMut) {
+        if !self.first_line {
+            out.put_slice(b"\r\n");
+        }
+
+        self.first_line = false;
+        out.put_slice(STANDARD.encode(input).as_bytes());
+    }
+}
+
+impl Encode for Base64Encoder {
+    fn encode(&mut self, mut input: &[u8], out: &mut BytesMut) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let len = input.len().min(BASE64_LINE_INPUT_LEN - self.pending.len());
+
+            self.pending.extend_from_slice(&input[..len]);
+            input = &input[len..];
+
+            if self.pending.len() < BASE64_LINE_INPUT_LEN {
+                return Ok(());
+            }
+
+            let line = std::mem::take(&mut self.pending);
+
+            self.write_line(&line, out);
+        }
+
+        let mut lines = input.chunks_exact(BASE64_LINE_INPUT_LEN);
+
+        for line in &mut lines {
+            self.write_line(line, out);
+        }
+
+        self.pending.extend_from_slice(lines.remainder());
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut BytesMut) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+
+            self.write_line(&line, out);
+        }
+
+        Ok(())
+    }
+}
+
+struct QuotedPrintableEncoder {
+    /// The length of the current encoded line.
+    line_len: usize,
+
+    /// A space or tab, which has to be encoded if it ends a line.
+    pending_space: Option<u8>,
+
+    /// A CR, which is a line break if it is followed by a LF.
+    pending_cr: bool,
+}
+
+impl QuotedPrintableEncoder {
+    /// Writes an encoded token, with a soft line break before it if it would
+    /// make the line too long.
+    fn write_token(&mut self, token: &[u8], out: &mut BytesMut) {
+        // Leaves room for the `=` of a soft line break.
+        if self.line_len + token.len() > MAX_LINE_LEN - 1 {
+            out.put_slice(b"=\r\n");
+            self.line_len = 0;
+        }
+
+        out.put_slice(token);
+        self.line_len += token.len();
+    }
+
+    fn write_encoded(&mut self, byte: u8, out: &mut BytesMut) {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+        self.write_token(
+            &[b'=', HEX[(byte >> 4) as usize], HEX[(byte & 0x0f) as usize]],
+            out,
+        );
+    }
+
+    /// Writes a pending space, which doesn't need to be encoded unless it is
+    /// at the end of a line.
+    fn flush_space(&mut self, end_of_line: bool, out: &mut BytesMut) {
+        if let Some(space) = self.pending_space.take() {
+            if end_of_line {
+                self.write_encoded(space, out);
+            } else {
+                self.write_token(&[space], out);
+            }
+        }
+    }
+}
+
+impl Encode for QuotedPrintableEncoder {
+    fn encode(&mut self, input: &[u8], out: &mut BytesMut) -> io::Result<()> {
+        for &byte in input {
+            if self.pending_cr {
+                self.pending_cr = false;
+
+                if byte == b'\n' {
+                    self.flush_space(true, out);
+                    out.put_slice(b"\r\n");
+                    self.line_len = 0;
+
+                    continue;
+                }
+
+                self.flush_space(false, out);
+                self.write_encoded(b'\r', out);
+            }
+
+            match byte {
+                b'\r' => {
+                    self.pending_cr = true;
+                    continue;
+                }
+                b' ' | b'\t' => {
+                    self.flush_space(false, out);
+                    self.pending_space = Some(byte);
+                }
+                b'!'..=b'<' | b'>'..=b'~' => {
+                    self.flush_space(false, out);
+                    self.write_token(&[byte], out);
+                }
+                _ => {
+                    self.flush_space(false, out);
+                    self.write_encoded(byte, out);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut BytesMut) -> io::Result<()> {
+        self.flush_space(true, out);
+
+        if self.pending_cr {
+            self.pending_cr = false;
+            self.write_encoded(b'\r', out);
+        }
+
+        Ok(())
+    }
+}
+
+fn decode_base64(input: &[u8]) -> Result<Vec<u8>, Error> {
+    let input: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+
+    STANDARD
+        .decode(input)
+        .map_err(|_| Error::Decode(TransferEncoding::Base64.as_str()))
+}
+
+/// Decodes quoted-printable content. Invalid escape sequences are kept as
+/// they are, as suggested by RFC 2045.
+///
+/// [See RFC2045 6.7](https://tools.ietf.org/html/rfc2045#section-6.7).
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    fn hex(byte: u8) -> Option<u8> {
+        (byte as char).to_digit(16).map(|d| d as u8)
+    }
+
+    let mut out = Vec::with_capacity(input.len());
+    let mut lines = input.split(|&b| b == b'\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        // Trailing whitespace may have been added in transport.
+        let end = line
+            .iter()
+            .rposition(|&b| b != b' ' && b != b'\t')
+            .map_or(0, |i| i + 1);
+        let line = &line[..end];
+
+        let (line, soft_break) = match line.strip_suffix(b"=") {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let mut i = 0;
+
+        while i < line.len() {
+            match (
+                line[i],
+                line.get(i + 1).copied().and_then(hex),
+                line.get(i + 2).copied().and_then(hex),
+            ) {
+                (b'=', Some(hi), Some(lo)) => {
+                    out.push(hi << 4 | lo);
+                    i += 3;
+                }
+                (byte, _, _) => {
+                    out.push(byte);
+                    i += 1;
+                }
+            }
+        }
+
+        if !soft_break && lines.peek().is_some() {
+            out.extend_from_slice(b"\r\n");
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncodedRead, TransferEncoding};
+    use futures_util::{
+        io::AsyncReadExt,
+        stream::{self, TryStreamExt},
+    };
+
+    async fn encode(encoding: TransferEncoding, chunks: &[&[u8]]) -> String {
+        let chunks: Vec<_> = chunks.iter().map(|chunk| Ok(chunk.to_vec())).collect();
+        let read = stream::iter(chunks).into_async_read();
+        let mut encoded = EncodedRead::new(Box::new(read), encoding.encoder());
+        let mut out = String::new();
+
+        encoded.read_to_string(&mut out).await.unwrap();
+
+        out
+    }
+
+    #[tokio::test]
+    async fn base64_wraps_lines() {
+        let input = [b'a'; 100];
+        let out = encode(TransferEncoding::Base64, &[&input[..30], &input[30..]]).await;
+        let lines: Vec<&str> = out.split("\r\n").collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 76);
+        assert_eq!(
+            TransferEncoding::Base64.decode(out.as_bytes()).unwrap(),
+            input.to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn quoted_printable_encodes_line_endings() {
+        let out = encode(
+            TransferEncoding::QuotedPrintable,
+            &[b"caf\xc3\xa9 \r", b"\nend \n="],
+        )
+        .await;
+
+        assert_eq!(out, "caf=C3=A9=20\r\nend =0A=3D");
+        assert_eq!(
+            TransferEncoding::QuotedPrintable
+                .decode(out.as_bytes())
+                .unwrap(),
+            b"caf\xc3\xa9 \r\nend \n=".to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn quoted_printable_wraps_long_lines() {
+        let input = [b'a'; 200];
+        let out = encode(TransferEncoding::QuotedPrintable, &[&input]).await;
+
+        assert!(out.split("\r\n").all(|line| line.len() <= 76));
+        assert_eq!(
+            TransferEncoding::QuotedPrintable
+                .decode(out.as_bytes())
+                .unwrap(),
+            input.to_vec()
+        );
+    }
+}
@@ -6,19 +6,258 @@
 // copied, modified, or distributed except according to those terms.
 //
 
-use std::io::Error as IoError;
+use crate::abort::AbortReport;
+use std::{
+    fmt,
+    io::{Error as IoError, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("Failed to write multipart content: {0:?}")]
-    ContentRead(IoError),
+    #[error("Failed to read multipart content{}: {source}", in_part(.part))]
+    ContentRead {
+        part: Option<PartContext>,
+        source: IoError,
+    },
+
+    #[error("Failed to open file {path:?}{}: {source}", in_part(.part))]
+    FileOpen {
+        part: Option<PartContext>,
+        path: PathBuf,
+        source: IoError,
+    },
+
+    #[error("File {path:?} changed from {len} to {actual} bytes while it was written{}", in_part(.part))]
+    FileChanged {
+        part: Option<PartContext>,
+        path: PathBuf,
+        len: u64,
+        actual: u64,
+    },
+
+    #[error("The boundary appears in the content of {part}")]
+    BoundaryCollision { part: PartContext },
+
+    #[error("Invalid {header} header in {part}")]
+    InvalidHeader { part: PartContext, header: String },
+
+    #[error("{limit} exceeded{}", in_part(.part))]
+    LimitExceeded {
+        part: Option<PartContext>,
+        limit: Limit,
+    },
+
+    #[error("{timeout} passed{}", in_part(.part))]
+    Timeout {
+        part: Option<PartContext>,
+        timeout: Timeout,
+    },
+
+    #[error("Failed to decode {0} content")]
+    Decode(&'static str),
+
+    #[error("Failed to parse multipart content: {0}")]
+    Parse(String),
+
+    #[error("Part {name:?} can't be urlencoded, only text/plain text parts can")]
+    NotUrlEncodable { name: String },
+
+    #[error("Invalid email address {address:?}")]
+    InvalidAddress { address: String },
+
+    #[error("Invalid header name {name:?}")]
+    InvalidHeaderName { name: String },
+
+    #[error("Invalid, or duplicate Content-ID {content_id:?}")]
+    InvalidContentId { content_id: String },
+
+    #[error("Upload path {path:?} doesn't exist in the operations")]
+    InvalidUploadPath { path: String },
+
+    #[error("Failed to build the request: {0}")]
+    Request(#[from] http::Error),
+
+    #[error("Writing multipart content was aborted")]
+    Aborted(AbortReport),
+
+    #[error("Failed to send chunk: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Returns the part the error happened in, if it happened while writing
+    /// a part.
+    pub fn part(&self) -> Option<&PartContext> {
+        match *self {
+            Error::ContentRead { ref part, .. }
+            | Error::FileOpen { ref part, .. }
+            | Error::FileChanged { ref part, .. }
+            | Error::LimitExceeded { ref part, .. }
+            | Error::Timeout { ref part, .. } => part.as_ref(),
+            Error::BoundaryCollision { ref part } | Error::InvalidHeader { ref part, .. } => {
+                Some(part)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a short name for the kind of error, like `content_read`, which
+    /// can be used as a metrics label.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Error::ContentRead { .. } => "content_read",
+            Error::FileOpen { .. } => "file_open",
+            Error::FileChanged { .. } => "file_changed",
+            Error::BoundaryCollision { .. } => "boundary_collision",
+            Error::InvalidHeader { .. } => "invalid_header",
+            Error::LimitExceeded { .. } => "limit_exceeded",
+            Error::Timeout { .. } => "timeout",
+            Error::Decode(_) => "decode",
+            Error::Parse(_) => "parse",
+            Error::NotUrlEncodable { .. } => "not_url_encodable",
+            Error::InvalidAddress { .. } => "invalid_address",
+            Error::InvalidHeaderName { .. } => "invalid_header_name",
+            Error::InvalidContentId { .. } => "invalid_content_id",
+            Error::InvalidUploadPath { .. } => "invalid_upload_path",
+            Error::Request(_) => "request",
+            Error::Aborted(_) => "aborted",
+            Error::Transport(_) => "transport",
+        }
+    }
+
+    /// Sets the part of an error that happened while opening, or mapping a
+    /// part.
+    pub(crate) fn in_part(mut self, context: PartContext) -> Error {
+        match self {
+            Error::ContentRead { ref mut part, .. }
+            | Error::FileOpen { ref mut part, .. }
+            | Error::FileChanged { ref mut part, .. } => *part = Some(context),
+            _ => (),
+        }
+
+        self
+    }
+}
+
+fn in_part(part: &Option<PartContext>) -> String {
+    match part {
+        Some(part) => format!(" in {}", part),
+        None => String::new(),
+    }
+}
+
+/// Where in a body an error happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartContext {
+    pub(crate) index: usize,
+
+    pub(crate) name: String,
+
+    pub(crate) filename: Option<String>,
+
+    pub(crate) offset: u64,
+}
+
+impl PartContext {
+    /// Returns the index of the part in the body, starting from 0. This
+    /// includes the `_charset_` part, if the form has a charset.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the name of the part.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the filename of the part, if it has one.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the offset in the content of the part, as it is written,
+    /// where the error happened.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl fmt::Display for PartContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "part {} {:?}", self.index, self.name)?;
+        if let Some(ref filename) = self.filename {
+            write!(f, " (filename {:?})", filename)?;
+        }
+        write!(f, " at byte {}", self.offset)
+    }
+}
+
+/// A limit on the size of a body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Limit {
+    /// The maximum length of the content of a part.
+    PartLen(u64),
+
+    /// The maximum length of the whole body.
+    BodyLen(u64),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Limit::PartLen(max) => write!(f, "Part length limit of {} bytes", max),
+            Limit::BodyLen(max) => write!(f, "Body length limit of {} bytes", max),
+        }
+    }
+}
+
+/// A timeout of a body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Timeout {
+    /// The longest a part's reader can go without producing content.
+    Idle(Duration),
+
+    /// The longest the whole body can take to write.
+    Total(Duration),
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Timeout::Idle(timeout) => write!(f, "Idle timeout of {:?}", timeout),
+            Timeout::Total(timeout) => write!(f, "Total timeout of {:?}", timeout),
+        }
+    }
 }
 
 impl From<Error> for IoError {
     fn from(val: Error) -> Self {
         match val {
-            Error::ContentRead(io) => io,
+            Error::ContentRead { source, .. } => source,
+            Error::FileOpen { source, .. } => source,
+            err @ (Error::Decode(_)
+            | Error::Parse(_)
+            | Error::FileChanged { .. }
+            | Error::BoundaryCollision { .. }
+            | Error::InvalidHeader { .. }) => IoError::new(ErrorKind::InvalidData, err.to_string()),
+            err @ (Error::NotUrlEncodable { .. }
+            | Error::LimitExceeded { .. }
+            | Error::InvalidAddress { .. }
+            | Error::InvalidHeaderName { .. }
+            | Error::InvalidContentId { .. }
+            | Error::InvalidUploadPath { .. }) => {
+                IoError::new(ErrorKind::InvalidInput, err.to_string())
+            }
+            err @ Error::Timeout { .. } => IoError::new(ErrorKind::TimedOut, err),
+            err @ Error::Aborted(_) => IoError::new(ErrorKind::ConnectionAborted, err),
+            err @ Error::Request(_) => IoError::new(ErrorKind::InvalidInput, err),
+            Error::Transport(err) => IoError::other(err),
         }
     }
 }
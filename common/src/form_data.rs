@@ -0,0 +1,451 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::{
+    charset::Charset, compression::Compression, encoding::TransferEncoding, error::Error,
+    metrics::Metrics, parser,
+};
+use bytes::Bytes;
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    HeaderMap,
+};
+use mime::Mime;
+
+/// A field of a received multipart/form-data body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    name: String,
+
+    filename: Option<String>,
+
+    content_type: Option<Mime>,
+
+    /// The content, decoded with the Content-Transfer-Encoding, and
+    /// Content-Encoding it was sent with.
+    content: Bytes,
+
+    /// The content as it was sent, if it had either encoding.
+    raw_content: Option<Bytes>,
+}
+
+impl Field {
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the filename the field was sent with.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the Content-Type the field was sent with.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns the content of the field, decoded if it was sent with a
+    /// Content-Transfer-Encoding, and decompressed if it was sent with a
+    /// Content-Encoding.
+    pub fn content(&self) -> &Bytes {
+        &self.content
+    }
+
+    /// Returns the content of the field as it was sent, before it was
+    /// decoded, and decompressed.
+    pub fn raw_content(&self) -> &Bytes {
+        self.raw_content.as_ref().unwrap_or(&self.content)
+    }
+}
+
+/// A received multipart/form-data body.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::ParsedForm;
+///
+/// let body = "--b\r\n\
+///     content-disposition: form-data; name=\"text\"\r\n\r\n\
+///     Hello World!\r\n--b--\r\n";
+///
+/// let form = ParsedForm::parse("multipart/form-data; boundary=b", body.into()).unwrap();
+///
+/// assert_eq!(form.text("text").unwrap().unwrap(), "Hello World!");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParsedForm {
+    fields: Vec<Field>,
+
+    /// The charset declared by the `_charset_` field, if any.
+    charset: Option<Charset>,
+}
+
+impl ParsedForm {
+    /// The most bytes the content of a field can be decoded to by `parse`.
+    pub const DEFAULT_MAX_DECODED_LEN: u64 = 64 * 1024 * 1024;
+
+    /// Parses a body from its Content-Type, and content. The content of a
+    /// field sent with a Content-Transfer-Encoding is decoded, and the
+    /// content of a field sent with a Content-Encoding is decompressed,
+    /// which fails if the encoding isn't enabled, or the content of a field
+    /// decodes to more than `DEFAULT_MAX_DECODED_LEN` bytes.
+    pub fn parse(content_type: &str, body: Bytes) -> Result<ParsedForm, Error> {
+        ParsedForm::parse_body(content_type, body, ParsedForm::DEFAULT_MAX_DECODED_LEN)
+    }
+
+    /// Parses a body like `parse`, but fails with `Error::LimitExceeded` if
+    /// the content of a field decodes to more than `max` bytes.
+    pub fn parse_with_max_decoded_len(
+        content_type: &str,
+        body: Bytes,
+        max: u64,
+    ) -> Result<ParsedForm, Error> {
+        ParsedForm::parse_body(content_type, body, max)
+    }
+
+    /// Parses a body like `parse`, and reports its length, its fields, or
+    /// the error it failed with to `metrics`.
+    pub fn parse_with_metrics(
+        content_type: &str,
+        body: Bytes,
+        metrics: &dyn Metrics,
+    ) -> Result<ParsedForm, Error> {
+        let len = body.len() as u64;
+
+        match ParsedForm::parse_body(content_type, body, ParsedForm::DEFAULT_MAX_DECODED_LEN) {
+            Ok(form) => {
+                metrics.bytes_parsed(len);
+                for field in &form.fields {
+                    metrics.field_parsed(&field.name, field.content.len() as u64);
+                }
+
+                Ok(form)
+            }
+            Err(err) => {
+                metrics.parse_error(&err);
+
+                Err(err)
+            }
+        }
+    }
+
+    fn parse_body(
+        content_type: &str,
+        body: Bytes,
+        max_decoded_len: u64,
+    ) -> Result<ParsedForm, Error> {
+        let boundary = parser::boundary(content_type)?;
+        let fields: Vec<Field> = parser::parse(&body, &boundary)?
+            .into_iter()
+            .map(|part| {
+                let name = part
+                    .disposition_param("name")
+                    .ok_or_else(|| Error::Parse("part without a name".to_string()))?;
+                let filename = part.disposition_param("filename");
+                let content_type = part
+                    .headers
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok());
+                let (content, raw_content) =
+                    match decode_content(&part.headers, &part.body, max_decoded_len)? {
+                        Some(content) => (content, Some(part.body)),
+                        None => (part.body, None),
+                    };
+
+                Ok(Field {
+                    name,
+                    filename,
+                    content_type,
+                    content,
+                    raw_content,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        // The value of `_charset_` is ASCII in any charset a form can use.
+        let charset = match fields.iter().find(|field| field.name == "_charset_") {
+            Some(field) => Some(
+                std::str::from_utf8(&field.content)
+                    .ok()
+                    .and_then(Charset::for_label)
+                    .ok_or_else(|| Error::Parse("unknown _charset_".to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(ParsedForm { fields, charset })
+    }
+
+    /// Returns the fields, in the order they were sent.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Returns the first field with the given name.
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the charset text fields without a charset parameter are
+    /// decoded with, which is the one declared by `_charset_`, or UTF-8.
+    ///
+    /// [See RFC7578 4.6](https://tools.ietf.org/html/rfc7578#section-4.6).
+    pub fn charset(&self) -> Charset {
+        self.charset.unwrap_or_default()
+    }
+
+    /// Returns the decoded text of the first field with the given name. The
+    /// field's charset parameter is used if it has one, otherwise the form's
+    /// charset.
+    pub fn text(&self, name: &str) -> Option<Result<String, Error>> {
+        let field = self.field(name)?;
+        let charset = match field
+            .content_type
+            .as_ref()
+            .and_then(|mime| mime.get_param(mime::CHARSET))
+        {
+            Some(label) => match Charset::for_label(label.as_str()) {
+                Some(charset) => charset,
+                None => {
+                    return Some(Err(Error::Parse(format!(
+                        "unknown charset {:?}",
+                        label.as_str()
+                    ))))
+                }
+            },
+            None => self.charset(),
+        };
+
+        Some(charset.decode(&field.content))
+    }
+}
+
+/// Decodes content with the encoding of its Content-Transfer-Encoding
+/// header, and then decompresses it with the encodings of its
+/// Content-Encoding header, in the reverse of the order they were applied.
+/// Returns `None` if it has neither, or they are identity encodings.
+///
+/// [See RFC2045 6](https://tools.ietf.org/html/rfc2045#section-6), and
+/// [RFC9110 8.4](https://tools.ietf.org/html/rfc9110#section-8.4).
+fn decode_content(
+    headers: &HeaderMap,
+    content: &Bytes,
+    max_len: u64,
+) -> Result<Option<Bytes>, Error> {
+    let mut decoded = None;
+
+    // The transfer encoding is applied last, so it is undone first.
+    if let Some(encoding) =
+        header_str(headers, "content-transfer-encoding")?.and_then(TransferEncoding::from_name)
+    {
+        decoded = Some(Bytes::from(encoding.decode(content)?));
+    }
+
+    let value = match header_str(headers, CONTENT_ENCODING.as_str())? {
+        Some(value) => value,
+        None => return Ok(decoded),
+    };
+
+    for name in value.rsplit(',').map(str::trim) {
+        if name.is_empty() || name.eq_ignore_ascii_case("identity") {
+            continue;
+        }
+
+        let compression = Compression::from_name(name)
+            .ok_or_else(|| Error::Parse(format!("unsupported content-encoding {:?}", name)))?;
+        let input = decoded.as_ref().unwrap_or(content);
+
+        decoded = Some(Bytes::from(compression.decode(input, max_len)?));
+    }
+
+    Ok(decoded)
+}
+
+/// Returns the value of a header, if the part has it.
+fn header_str<'h>(headers: &'h HeaderMap, name: &str) -> Result<Option<&'h str>, Error> {
+    headers
+        .get(name)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| Error::Parse(format!("invalid {}", name)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParsedForm;
+    use crate::{charset::Charset, client_::Form, metrics::tests::RecordingMetrics};
+    use futures_util::TryStreamExt;
+
+    #[tokio::test]
+    async fn parse_reads_written_form() {
+        let mut form = Form::default();
+
+        form.set_charset(Charset::UTF_8);
+        form.add_text("text", "Hello World!");
+
+        let content_type = form.content_type();
+        let body = crate::client_::Body::from(form)
+            .try_concat()
+            .await
+            .unwrap()
+            .freeze();
+        let parsed = ParsedForm::parse(&content_type, body).unwrap();
+
+        assert_eq!(parsed.fields().len(), 2);
+        assert_eq!(parsed.charset(), Charset::UTF_8);
+        assert_eq!(parsed.text("text").unwrap().unwrap(), "Hello World!");
+        assert!(parsed.text("missing").is_none());
+    }
+
+    #[test]
+    fn parse_with_metrics_reports_fields_and_errors() {
+        let metrics = RecordingMetrics::default();
+        let body = "--b\r\n\
+            content-disposition: form-data; name=\"text\"\r\n\r\n\
+            Hello\r\n--b--\r\n";
+
+        ParsedForm::parse_with_metrics("multipart/form-data; boundary=b", body.into(), &metrics)
+            .unwrap();
+        assert!(ParsedForm::parse_with_metrics("text/plain", body.into(), &metrics).is_err());
+
+        assert_eq!(
+            *metrics.events.lock().unwrap(),
+            [
+                format!("bytes_parsed {}", body.len()),
+                "field_parsed text 5".to_string(),
+                "parse_error parse".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(all(feature = "deflate", feature = "brotli", feature = "zstd"))]
+    #[tokio::test]
+    async fn parse_decompresses_content_encoding() {
+        use crate::{client_::Part, compression::Compression};
+
+        let text = "Hello World! ".repeat(64);
+
+        for compression in [Compression::Deflate, Compression::Brotli, Compression::Zstd] {
+            let mut form = Form::default();
+
+            form.add_part(Part::text("text", text.clone()).with_compression(compression));
+
+            let content_type = form.content_type();
+            let body = crate::client_::Body::from(form)
+                .try_concat()
+                .await
+                .unwrap()
+                .freeze();
+            let parsed = ParsedForm::parse(&content_type, body).unwrap();
+            let field = parsed.field("text").unwrap();
+
+            assert_eq!(field.content(), text.as_bytes(), "{:?}", compression);
+            assert_eq!(
+                compression.decode(field.raw_content(), u64::MAX).unwrap(),
+                text.as_bytes()
+            );
+            assert_eq!(parsed.text("text").unwrap().unwrap(), text);
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_decodes_content_transfer_encoding() {
+        use crate::{client_::Part, encoding::TransferEncoding};
+
+        let text = "Hello World! ".repeat(16);
+
+        for encoding in [TransferEncoding::Base64, TransferEncoding::QuotedPrintable] {
+            let mut form = Form::default();
+            let part = Part::text("text", text.clone()).with_transfer_encoding(encoding);
+
+            #[cfg(feature = "deflate")]
+            let part = part.with_compression(crate::compression::Compression::Deflate);
+
+            form.add_part(part);
+
+            let content_type = form.content_type();
+            let body = crate::client_::Body::from(form)
+                .try_concat()
+                .await
+                .unwrap()
+                .freeze();
+            let parsed = ParsedForm::parse(&content_type, body).unwrap();
+            let field = parsed.field("text").unwrap();
+
+            assert_eq!(field.content(), text.as_bytes(), "{:?}", encoding);
+            assert_ne!(field.raw_content(), text.as_bytes());
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn parse_with_max_decoded_len_rejects_larger_content() {
+        use crate::{
+            client_::Part,
+            compression::Compression,
+            error::{Error, Limit},
+        };
+
+        let mut form = Form::default();
+
+        form.add_part(Part::text("text", "0".repeat(1 << 20)).with_compression(Compression::Gzip));
+
+        let content_type = form.content_type();
+        let body = crate::client_::Body::from(form)
+            .try_concat()
+            .await
+            .unwrap()
+            .freeze();
+
+        assert!(
+            ParsedForm::parse_with_max_decoded_len(&content_type, body.clone(), 1 << 20).is_ok()
+        );
+        assert!(matches!(
+            ParsedForm::parse_with_max_decoded_len(&content_type, body, 4096),
+            Err(Error::LimitExceeded {
+                part: None,
+                limit: Limit::PartLen(4096),
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_content_encoding() {
+        let body = "--b\r\n\
+            content-disposition: form-data; name=\"text\"\r\n\
+            content-encoding: compress\r\n\r\n\
+            Hello\r\n--b--\r\n";
+
+        assert_eq!(
+            ParsedForm::parse("multipart/form-data; boundary=b", body.into())
+                .unwrap_err()
+                .kind(),
+            "parse"
+        );
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn text_is_decoded_with_charset_field() {
+        let body = b"--b\r\n\
+            content-disposition: form-data; name=\"_charset_\"\r\n\r\n\
+            Shift_JIS\r\n--b\r\n\
+            content-disposition: form-data; name=\"text\"\r\n\r\n\
+            \x93\xfa\x96\x7b\x8c\xea\r\n--b--\r\n";
+        let parsed =
+            ParsedForm::parse("multipart/form-data; boundary=b", body.to_vec().into()).unwrap();
+
+        assert_eq!(parsed.text("text").unwrap().unwrap(), "日本語");
+    }
+}
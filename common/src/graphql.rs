@@ -0,0 +1,391 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! File uploads in GraphQL requests.
+//!
+//! [See](https://github.com/jaydenseric/graphql-multipart-request-spec).
+
+use crate::{
+    client_::{Form, Part},
+    error::Error,
+    parser::{self, ParsedPart},
+};
+use bytes::Bytes;
+use http::header::CONTENT_TYPE;
+use mime::Mime;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Builds the form of a GraphQL request with file uploads.
+///
+/// The form contains the `operations` field, the `map` field, and then a
+/// part for each file, named by its position.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::{GraphqlForm, Part};
+/// use serde_json::json;
+/// use std::io::Cursor;
+///
+/// let mut request = GraphqlForm::new(json!({
+///     "query": "mutation ($file: Upload!) { upload(file: $file) { id } }",
+///     "variables": { "file": null },
+/// }));
+///
+/// request
+///     .add_upload(
+///         "variables.file",
+///         Part::reader("file", Cursor::new("Hello World!")).with_filename("hello.txt"),
+///     )
+///     .unwrap();
+///
+/// let form = request.into_form();
+/// ```
+pub struct GraphqlForm<'a> {
+    /// The operations, with each upload path set to null.
+    operations: Value,
+
+    /// The uploads, and the paths in the operations they are used at.
+    uploads: Vec<(Vec<String>, Part<'a>)>,
+}
+
+impl<'a> GraphqlForm<'a> {
+    /// Creates a request for the operations, which is either a single
+    /// operation, or an array of operations for a batch.
+    pub fn new(operations: Value) -> GraphqlForm<'a> {
+        GraphqlForm {
+            operations,
+            uploads: vec![],
+        }
+    }
+
+    /// Adds a file that is used at the path of a variable, like
+    /// `variables.file`, `variables.files.0`, or `0.variables.file` in a
+    /// batch. The variable is set to null in the operations. Fails if the
+    /// path doesn't exist in the operations.
+    pub fn add_upload<P>(&mut self, path: P, part: Part<'a>) -> Result<(), Error>
+    where
+        P: Into<String>,
+    {
+        self.add_shared_upload(vec![path.into()], part)
+    }
+
+    /// Adds a file that is used at several paths, which is only sent once.
+    /// Fails if any of the paths doesn't exist in the operations, in which
+    /// case the operations aren't changed.
+    pub fn add_shared_upload<I, P>(&mut self, paths: I, part: Part<'a>) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        let paths: Vec<String> = paths.into_iter().map(Into::into).collect();
+
+        if let Some(path) = paths
+            .iter()
+            .find(|path| self.operations.pointer(&pointer(path)).is_none())
+        {
+            return Err(Error::InvalidUploadPath { path: path.clone() });
+        }
+
+        for path in paths.iter() {
+            set_path(&mut self.operations, path, Value::Null);
+        }
+
+        self.uploads.push((paths, part));
+
+        Ok(())
+    }
+
+    /// Builds the form.
+    pub fn into_form(self) -> Form<'a> {
+        let map: Map<String, Value> = self
+            .uploads
+            .iter()
+            .enumerate()
+            .map(|(i, (paths, _))| (i.to_string(), Value::from(paths.clone())))
+            .collect();
+
+        let mut form = Form::default();
+
+        form.add_part(
+            Part::text("operations", self.operations.to_string()).with_mime(mime::APPLICATION_JSON),
+        );
+        form.add_part(
+            Part::text("map", Value::Object(map).to_string()).with_mime(mime::APPLICATION_JSON),
+        );
+        for (i, (_, mut part)) in self.uploads.into_iter().enumerate() {
+            part.set_name(&i.to_string());
+            form.add_part(part);
+        }
+
+        form
+    }
+}
+
+/// A file received in a GraphQL request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upload {
+    filename: Option<String>,
+
+    content_type: Option<Mime>,
+
+    content: Bytes,
+}
+
+impl Upload {
+    /// Returns the filename the file was sent with.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the mime type the file was sent with.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns the content of the file.
+    pub fn content(&self) -> &Bytes {
+        &self.content
+    }
+}
+
+/// A GraphQL request with file uploads, reassembled from a received
+/// multipart body.
+///
+/// Each path an upload is used at is set to the index of the upload in
+/// `GraphqlRequest::uploads`.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::GraphqlRequest;
+///
+/// let body = "--b\r\n\
+///     content-disposition: form-data; name=\"operations\"\r\n\r\n\
+///     {\"variables\":{\"file\":null}}\r\n--b\r\n\
+///     content-disposition: form-data; name=\"map\"\r\n\r\n\
+///     {\"0\":[\"variables.file\"]}\r\n--b\r\n\
+///     content-disposition: form-data; name=\"0\"; filename=\"a.txt\"\r\n\r\n\
+///     Hello World!\r\n--b--\r\n";
+///
+/// let request = GraphqlRequest::parse("multipart/form-data; boundary=b", body.into()).unwrap();
+///
+/// assert_eq!(request.operations()["variables"]["file"], 0);
+/// assert_eq!(request.uploads()[0].content(), "Hello World!");
+/// ```
+#[derive(Clone, Debug)]
+pub struct GraphqlRequest {
+    operations: Value,
+
+    uploads: Vec<Upload>,
+}
+
+impl GraphqlRequest {
+    /// Parses a request from its Content-Type, and body.
+    pub fn parse(content_type: &str, body: Bytes) -> Result<GraphqlRequest, Error> {
+        let boundary = parser::boundary(content_type)?;
+        let mut parts = parser::parse(&body, &boundary)?.into_iter();
+
+        // The spec requires the operations, and map to be sent first, so
+        // that a server can start processing before the files arrive.
+        let mut operations = json_field(parts.next(), "operations")?;
+        let map = match json_field(parts.next(), "map")? {
+            Value::Object(map) => map,
+            _ => return Err(Error::Parse("map is not an object".to_string())),
+        };
+
+        let mut files: HashMap<String, ParsedPart> = parts
+            .filter_map(|part| part.disposition_param("name").map(|name| (name, part)))
+            .collect();
+        let mut uploads = vec![];
+
+        for (key, paths) in map {
+            let part = files
+                .remove(&key)
+                .ok_or_else(|| Error::Parse(format!("missing file {:?}", key)))?;
+            let paths = match paths {
+                Value::Array(paths) => paths,
+                _ => {
+                    return Err(Error::Parse(format!(
+                        "paths of file {:?} are not an array",
+                        key
+                    )))
+                }
+            };
+
+            for path in paths {
+                let path = path.as_str().ok_or_else(|| {
+                    Error::Parse(format!("path of file {:?} is not a string", key))
+                })?;
+
+                if !set_path(&mut operations, path, Value::from(uploads.len())) {
+                    return Err(Error::Parse(format!("invalid path {:?}", path)));
+                }
+            }
+
+            uploads.push(Upload {
+                filename: part.disposition_param("filename"),
+                content_type: part
+                    .headers
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok()),
+                content: part.body,
+            });
+        }
+
+        Ok(GraphqlRequest {
+            operations,
+            uploads,
+        })
+    }
+
+    /// Returns the operations, with each upload replaced by its index.
+    pub fn operations(&self) -> &Value {
+        &self.operations
+    }
+
+    /// Returns the uploaded files.
+    pub fn uploads(&self) -> &[Upload] {
+        &self.uploads
+    }
+
+    /// Returns the operations, and the uploaded files.
+    pub fn into_parts(self) -> (Value, Vec<Upload>) {
+        (self.operations, self.uploads)
+    }
+}
+
+/// Parses a part that has to be the JSON field with the given name.
+fn json_field(part: Option<ParsedPart>, name: &str) -> Result<Value, Error> {
+    let part = part
+        .filter(|part| part.disposition_param("name").as_deref() == Some(name))
+        .ok_or_else(|| Error::Parse(format!("expected the {} field", name)))?;
+
+    serde_json::from_slice(&part.body).map_err(|e| Error::Parse(format!("invalid {}: {}", name, e)))
+}
+
+/// Sets the value at a dot separated path of object keys, and array
+/// indexes. Returns false if the path doesn't exist.
+fn set_path(value: &mut Value, path: &str, new: Value) -> bool {
+    match value.pointer_mut(&pointer(path)) {
+        Some(target) => {
+            *target = new;
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Converts a dot separated path to a JSON pointer.
+///
+/// [See RFC6901](https://tools.ietf.org/html/rfc6901).
+fn pointer(path: &str) -> String {
+    path.split('.').fold(String::new(), |mut out, segment| {
+        out.push('/');
+        out.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GraphqlForm, GraphqlRequest};
+    use crate::{
+        client_::{Body, Part},
+        error::Error,
+    };
+    use futures_util::TryStreamExt;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn into_form_is_reassembled_by_server() {
+        let mut request = GraphqlForm::new(json!([
+            { "variables": { "file": "ignored" } },
+            { "variables": { "files": [null, null] } },
+        ]));
+
+        request
+            .add_shared_upload(
+                ["0.variables.file", "1.variables.files.1"],
+                Part::reader("a", Cursor::new("a")).with_filename("a.txt"),
+            )
+            .unwrap();
+        request
+            .add_upload(
+                "1.variables.files.0",
+                Part::reader("b", Cursor::new("b")).with_mime(mime::IMAGE_PNG),
+            )
+            .unwrap();
+
+        let form = request.into_form();
+        let content_type = form.content_type();
+        let body = Body::from(form).try_concat().await.unwrap().freeze();
+        let body_str = String::from_utf8_lossy(&body);
+
+        let operations = body_str.find("name=\"operations\"").unwrap();
+        let map = body_str.find("name=\"map\"").unwrap();
+        let first = body_str.find("name=\"0\"").unwrap();
+        let second = body_str.find("name=\"1\"").unwrap();
+
+        assert!(operations < map && map < first && first < second);
+
+        let request = GraphqlRequest::parse(&content_type, body).unwrap();
+
+        assert_eq!(
+            request.operations(),
+            &json!([
+                { "variables": { "file": 0 } },
+                { "variables": { "files": [1, 0] } },
+            ])
+        );
+        assert_eq!(request.uploads()[0].filename(), Some("a.txt"));
+        assert_eq!(request.uploads()[0].content(), "a");
+        assert_eq!(request.uploads()[1].content_type(), Some(&mime::IMAGE_PNG));
+        assert_eq!(request.uploads()[1].content(), "b");
+    }
+
+    #[test]
+    fn add_upload_rejects_missing_paths() {
+        let operations = json!({ "variables": { "file": "a", "files": [null] } });
+        let mut request = GraphqlForm::new(operations.clone());
+
+        for path in ["variables.missing", "variables.files.1", "variables.file.0"] {
+            let err = request
+                .add_upload(path, Part::reader("a", Cursor::new("a")))
+                .unwrap_err();
+
+            assert_eq!(err.kind(), "invalid_upload_path", "{}", path);
+        }
+
+        let err = request
+            .add_shared_upload(
+                ["variables.file", "variables.files.1"],
+                Part::reader("a", Cursor::new("a")),
+            )
+            .unwrap_err();
+
+        assert!(matches!(err, Error::InvalidUploadPath { path } if path == "variables.files.1"));
+        assert_eq!(request.operations, operations);
+        assert!(request.uploads.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_missing_file() {
+        let body = "--b\r\n\
+            content-disposition: form-data; name=\"operations\"\r\n\r\n\
+            {\"variables\":{\"file\":null}}\r\n--b\r\n\
+            content-disposition: form-data; name=\"map\"\r\n\r\n\
+            {\"0\":[\"variables.file\"]}\r\n--b--\r\n";
+
+        assert!(GraphqlRequest::parse("multipart/form-data; boundary=b", body.into()).is_err());
+    }
+}
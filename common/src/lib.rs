@@ -39,20 +39,120 @@
 //! ```rust
 //! use hyper_multipart_rfc7578 as multipart;
 //! ```
+//!
+//! ## Features
+//!
+//! * `tokio`: Adds `Form::add_file_async`, which reads files on tokio's
+//!   blocking thread pool, `Form::offload_sync_readers`, which does the
+//!   same for every sync reader in a form, and `TokioTimer`, which a
+//!   `RateLimiter`, and `Timeouts` can wait on.
+//! * `async-std`: Adds `Form::add_file_async`, which reads files on
+//!   async-std's blocking thread pool, and `AsyncStdTimer`. If both are
+//!   enabled, tokio is used to read files.
+//! * `encoding`: Adds every `Charset` in the WHATWG Encoding Standard, like
+//!   Shift_JIS, and windows-1252, for encoding, and decoding text fields.
+//! * `gzip`, `deflate`, `brotli`, `zstd`: Enable the corresponding
+//!   `Compression` algorithm, which can be applied to a part with
+//!   `Part::with_compression`.
+//! * `md5`, `sha2`, `crc32c`: Enable the corresponding `ChecksumAlgorithm`,
+//!   which can be computed over a part with `Part::with_checksum`.
+//! * `directory`: Adds `Form::add_directory`, which adds every file in a
+//!   directory, filtered with `DirectoryOptions`.
+//! * `graphql`: Adds `GraphqlForm`, which builds a GraphQL request with file
+//!   uploads, and `GraphqlRequest`, which reassembles one on a server.
+//! * `metrics`: Adds `MetricsFacade`, which records `Metrics` with the
+//!   `metrics` crate, to export them to Prometheus, or another system.
+//! * `mime-message`: Adds the `mime_message` module, which writes email
+//!   messages with attachments.
+//! * `mmap`: Adds `Form::map_files`, which writes large files from a memory
+//!   mapping, rather than reading them, and `Body::poll_bytes` returns
+//!   slices of the mapping without copying them.
+//! * `s3`: Adds `PostPolicy`, which builds the signed form of a
+//!   browser-based POST upload to S3, or an S3-compatible store.
+//! * `test-util`: Adds the `test_util` module, with boundary generators for
+//!   byte-exact bodies, and a pretty-printer for multipart bodies, and
+//!   `Form::encode_to_vec`.
+//! * `tracing`: Writes each body in a `multipart_body` span, and each part in
+//!   a `multipart_part` span, with the field name, filename, content type,
+//!   bytes written, and duration, and emits events for reader errors, and
+//!   readers that stall. Adds `Body::span`.
 
+mod abort;
+mod batch;
 mod boundary;
+mod charset;
+mod checksum;
+mod chunked;
 mod client_;
+mod compression;
+mod curl;
+mod date;
+#[cfg(feature = "directory")]
+mod directory;
+mod encoding;
 mod error;
+mod form_data;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "mime-message")]
+mod message;
+mod metrics;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod parser;
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+mod runtime;
+#[cfg(feature = "s3")]
+mod s3;
+// The crate's own tests use the test utilities too.
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+mod throttle;
+mod timeout;
+mod trace;
+mod urlencoded;
 
 pub mod client {
-    pub use crate::error::Error;
+    pub use crate::error::{Error, Limit, PartContext, Timeout};
 
     /// This module contains data structures for building a multipart/form
     /// body to send a server.
     pub mod multipart {
         pub use crate::{
+            abort::{AbortHandle, AbortReport},
+            batch::Batch,
             boundary::BoundaryGenerator,
-            client_::{Body, Form},
+            charset::Charset,
+            checksum::{Checksum, ChecksumAlgorithm, DigestFormat, PartChecksum},
+            chunked::{Chunk, ChunkField, ChunkedUpload, SendFuture, Transport},
+            client_::{Body, Form, Part},
+            compression::Compression,
+            encoding::TransferEncoding,
+            form_data::{Field, ParsedForm},
+            metrics::Metrics,
+            throttle::{RateLimiter, Sleep, Timer},
+            timeout::Timeouts,
+            urlencoded::FormEncoding,
         };
+
+        #[cfg(feature = "directory")]
+        pub use crate::directory::{DirectoryOptions, SymlinkPolicy};
+        #[cfg(feature = "graphql")]
+        pub use crate::graphql::{GraphqlForm, GraphqlRequest, Upload};
+        #[cfg(feature = "metrics")]
+        pub use crate::metrics::MetricsFacade;
+        #[cfg(feature = "s3")]
+        pub use crate::s3::{Credentials, PolicyCondition, PostPolicy};
+        #[cfg(feature = "async-std")]
+        pub use crate::throttle::AsyncStdTimer;
+        #[cfg(feature = "tokio")]
+        pub use crate::throttle::TokioTimer;
     }
 }
+
+/// This module contains data structures for writing an email message, with
+/// alternative text, and HTML bodies, inline images, and attachments.
+#[cfg(feature = "mime-message")]
+pub mod mime_message {
+    pub use crate::message::{Mailbox, Message, MessageBody};
+}
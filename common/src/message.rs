@@ -0,0 +1,829 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Email messages with attachments, written with the multipart body.
+//!
+//! [See RFC5322](https://tools.ietf.org/html/rfc5322), and
+//! [RFC2045](https://tools.ietf.org/html/rfc2045).
+
+use crate::{
+    boundary::BoundaryGenerator,
+    client_::{Body, Form, Part},
+    date::civil_from_days,
+    encoding::TransferEncoding,
+    error::Error,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::{BufMut, BytesMut};
+use futures_core::Stream;
+use futures_util::{io::AsyncRead, TryStreamExt};
+use http::header::{HeaderName, HeaderValue, CONTENT_DISPOSITION};
+use mime::Mime;
+use rand::{distributions::Alphanumeric, rngs::SmallRng, Rng, SeedableRng};
+use std::{
+    fmt::{self, Display, Write},
+    io::{self, Read},
+    pin::Pin,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The longest line of a header that is folded.
+const MAX_LINE_LEN: usize = 76;
+
+/// Generates boundaries long enough to never appear in the content of a
+/// message, including the content of nested multiparts.
+struct MessageBoundary;
+
+impl BoundaryGenerator for MessageBoundary {
+    fn generate_boundary() -> String {
+        let rng = SmallRng::from_entropy();
+        let ascii = rng.sample_iter(&Alphanumeric);
+
+        String::from_iter(ascii.map(|b| b as char).take(24))
+    }
+}
+
+/// An email address, with an optional display name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mailbox {
+    name: Option<String>,
+
+    address: String,
+}
+
+impl Mailbox {
+    /// Creates a mailbox with just an address. Fails if the address is
+    /// empty, or has angle brackets, whitespace, or control characters,
+    /// which would end the address, or the header it is written in.
+    pub fn new<A>(address: A) -> Result<Mailbox, Error>
+    where
+        A: Into<String>,
+    {
+        let address = address.into();
+        let invalid = |c: char| c == '<' || c == '>' || c.is_whitespace() || c.is_control();
+
+        if address.is_empty() || address.contains(invalid) {
+            return Err(Error::InvalidAddress { address });
+        }
+
+        Ok(Mailbox {
+            name: None,
+            address,
+        })
+    }
+
+    /// Sets the display name, which is encoded if it isn't printable ASCII.
+    pub fn with_name<N>(mut self, name: N) -> Mailbox
+    where
+        N: Into<String>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+impl Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) if is_plain(name) => {
+                f.write_char('"')?;
+                for c in name.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_char('\\')?;
+                    }
+                    f.write_char(c)?;
+                }
+                write!(f, "\" <{}>", self.address)
+            }
+            Some(ref name) => write!(f, "{} <{}>", encode_word(name), self.address),
+            None => write!(f, "<{}>", self.address),
+        }
+    }
+}
+
+/// An email message.
+///
+/// The body is written as a `multipart/alternative` of the text, and HTML
+/// versions, in a `multipart/related` with the inline images, in a
+/// `multipart/mixed` with the attachments. Containers that aren't needed
+/// are left out.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::mime_message::{Mailbox, Message};
+/// use std::io::Cursor;
+///
+/// let from = Mailbox::new("ferris@example.com").unwrap().with_name("Ferris");
+/// let mut message = Message::new(from);
+///
+/// message.add_to(Mailbox::new("crab@example.com").unwrap());
+/// message.set_subject("Report");
+/// message.set_text("See the attached report.");
+/// message.set_html("<p>See the attached <b>report</b>.</p>");
+/// message.add_attachment("report.csv", mime::TEXT_CSV, Cursor::new("a,b\n1,2\n"));
+///
+/// let body = message.into_body();
+/// ```
+pub struct Message<'a> {
+    /// The address headers, in the order they were first added, with their
+    /// mailboxes. They are folded when the body is built.
+    addresses: Vec<(&'static str, Vec<Mailbox>)>,
+
+    /// The other top level headers, each a folded line without the line
+    /// break.
+    headers: Vec<String>,
+
+    text: Option<String>,
+
+    html: Option<String>,
+
+    /// Images referenced from the HTML by their Content-ID.
+    inline: Vec<Entity<'a>>,
+
+    attachments: Vec<Entity<'a>>,
+}
+
+impl<'a> Message<'a> {
+    /// Creates a message from the given sender.
+    pub fn new(from: Mailbox) -> Message<'a> {
+        let mut message = Message {
+            addresses: vec![],
+            headers: vec![],
+            text: None,
+            html: None,
+            inline: vec![],
+            attachments: vec![],
+        };
+
+        message.add_address_header("From", from);
+        message
+    }
+
+    /// Adds a recipient.
+    pub fn add_to(&mut self, to: Mailbox) {
+        self.add_address_header("To", to);
+    }
+
+    /// Adds a carbon copy recipient.
+    pub fn add_cc(&mut self, cc: Mailbox) {
+        self.add_address_header("Cc", cc);
+    }
+
+    /// Sets the subject, replacing any subject set before. It is encoded if
+    /// it isn't printable ASCII.
+    pub fn set_subject<S>(&mut self, subject: S)
+    where
+        S: AsRef<str>,
+    {
+        self.headers.retain(|header| !is_header(header, "Subject"));
+        self.push_header("Subject", subject.as_ref());
+    }
+
+    /// Adds a header, like `Message-ID`, or `Reply-To`. A value that isn't
+    /// printable ASCII is encoded, so structured values, like addresses,
+    /// should be printable ASCII. Fails if the name isn't a valid header
+    /// name.
+    ///
+    /// A `Date` header with the time the body is built is added, unless one
+    /// is added here.
+    pub fn add_header<N, V>(&mut self, name: N, value: V) -> Result<(), Error>
+    where
+        N: Display,
+        V: AsRef<str>,
+    {
+        let name = name.to_string();
+
+        // RFC5322 field names are printable ASCII, except the colon.
+        if name.is_empty() || !name.bytes().all(|b| b.is_ascii_graphic() && b != b':') {
+            return Err(Error::InvalidHeaderName { name });
+        }
+
+        self.push_header(&name, value.as_ref());
+
+        Ok(())
+    }
+
+    /// Adds a header with an unstructured value.
+    fn push_header(&mut self, name: &str, value: &str) {
+        let value = if is_plain(value) {
+            value.to_string()
+        } else {
+            encode_word(value)
+        };
+
+        self.headers
+            .push(fold(&format!("{}: ", name), value.split(' '), " "));
+    }
+
+    /// Sets the plain text version of the body.
+    pub fn set_text<T>(&mut self, text: T)
+    where
+        T: AsRef<str>,
+    {
+        self.text = Some(normalize_line_endings(text.as_ref()));
+    }
+
+    /// Sets the HTML version of the body.
+    pub fn set_html<T>(&mut self, html: T)
+    where
+        T: AsRef<str>,
+    {
+        self.html = Some(normalize_line_endings(html.as_ref()));
+    }
+
+    /// Adds an attachment, which is base64 encoded.
+    pub fn add_attachment<F, R>(&mut self, filename: F, mime: Mime, read: R)
+    where
+        F: AsRef<str>,
+        R: 'a + Read + Send + Unpin,
+    {
+        let part = attachment(
+            "attachment",
+            filename.as_ref(),
+            mime,
+            Part::reader("", read),
+        );
+
+        self.attachments.push(Entity::Part(part));
+    }
+
+    /// Adds an attachment that is read asynchronously.
+    pub fn add_async_attachment<F, R>(&mut self, filename: F, mime: Mime, read: R)
+    where
+        F: AsRef<str>,
+        R: 'a + AsyncRead + Send + Unpin,
+    {
+        let part = attachment(
+            "attachment",
+            filename.as_ref(),
+            mime,
+            Part::async_reader("", read),
+        );
+
+        self.attachments.push(Entity::Part(part));
+    }
+
+    /// Adds an image that the HTML references as `cid:<content_id>`.
+    pub fn add_inline<C, R>(&mut self, content_id: C, mime: Mime, read: R)
+    where
+        C: AsRef<str>,
+        R: 'a + Read + Send + Unpin,
+    {
+        let content_id = content_id.as_ref();
+        let part = attachment("inline", content_id, mime, Part::reader("", read));
+
+        let part = match HeaderValue::from_str(&format!("<{}>", content_id)) {
+            Ok(value) => part.with_header(HeaderName::from_static("content-id"), value),
+            Err(_) => part,
+        };
+
+        self.inline.push(Entity::Part(part));
+    }
+
+    /// Builds the message, which is streamed with CRLF line endings, ready
+    /// to be sent by an SMTP client.
+    pub fn into_body(self) -> MessageBody<'a> {
+        let mut alternative = vec![];
+
+        if let Some(text) = self.text {
+            alternative.push(text_part(text, mime::TEXT_PLAIN_UTF_8));
+        }
+        if let Some(html) = self.html {
+            alternative.push(text_part(html, mime::TEXT_HTML_UTF_8));
+        }
+
+        let mut body = vec![];
+
+        if alternative.len() > 1 {
+            body.push(multipart("alternative", alternative));
+        } else {
+            body.extend(alternative);
+        }
+        if !self.inline.is_empty() {
+            body.extend(self.inline);
+            body = vec![multipart("related", body)];
+        }
+        body.extend(self.attachments);
+
+        // The top level is always multipart, so that it can be written by
+        // the multipart body.
+        let form = match body.pop() {
+            Some(Entity::Multipart(form)) if body.is_empty() => form,
+            Some(last) => {
+                body.push(last);
+                form_of("mixed", body)
+            }
+            None => form_of("mixed", body),
+        };
+
+        let mut headers = BytesMut::new();
+
+        // The Date header is required.
+        //
+        // [See RFC5322 3.6](https://tools.ietf.org/html/rfc5322#section-3.6).
+        if !self.headers.iter().any(|header| is_header(header, "Date")) {
+            headers.put_slice(format!("Date: {}\r\n", format_date(SystemTime::now())).as_bytes());
+        }
+        for (name, mailboxes) in self.addresses {
+            let addresses: Vec<String> = mailboxes.iter().map(Mailbox::to_string).collect();
+            let header = fold(
+                &format!("{}: ", name),
+                addresses.iter().map(String::as_str),
+                ", ",
+            );
+
+            headers.put_slice(header.as_bytes());
+            headers.put_slice(b"\r\n");
+        }
+        for header in self.headers {
+            headers.put_slice(header.as_bytes());
+            headers.put_slice(b"\r\n");
+        }
+        headers.put_slice(b"MIME-Version: 1.0\r\n");
+        headers.put_slice(format!("Content-Type: {}\r\n\r\n", form.content_type()).as_bytes());
+
+        MessageBody {
+            headers: Some(headers),
+            body: Body::from(form),
+        }
+    }
+
+    /// Adds a mailbox to a header with a list of addresses, which is added
+    /// if it doesn't have one yet.
+    fn add_address_header(&mut self, name: &'static str, mailbox: Mailbox) {
+        match self.addresses.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, mailboxes)) => mailboxes.push(mailbox),
+            None => self.addresses.push((name, vec![mailbox])),
+        }
+    }
+}
+
+/// A nested multipart, or a single part of a message.
+enum Entity<'a> {
+    Part(Part<'a>),
+
+    Multipart(Form<'a>),
+}
+
+impl<'a> Entity<'a> {
+    fn into_part(self) -> Part<'a> {
+        match self {
+            Entity::Part(part) => part,
+            Entity::Multipart(form) => {
+                let mime = form
+                    .content_type()
+                    .parse()
+                    .expect("a multipart content type with an alphanumeric boundary is valid");
+                let read = Body::from(form).map_err(io::Error::from).into_async_read();
+
+                Part::async_reader("", read).with_mime(mime)
+            }
+        }
+    }
+}
+
+/// Creates a multipart form of the given subtype.
+fn form_of<'a>(subtype: &str, entities: Vec<Entity<'a>>) -> Form<'a> {
+    let mut form = Form::new::<MessageBoundary>();
+
+    form.set_subtype(subtype);
+    for entity in entities {
+        form.add_part(entity.into_part());
+    }
+
+    form
+}
+
+/// Creates a nested multipart of the given subtype.
+fn multipart<'a>(subtype: &str, entities: Vec<Entity<'a>>) -> Entity<'a> {
+    Entity::Multipart(form_of(subtype, entities))
+}
+
+/// A text part, which is quoted-printable encoded.
+fn text_part<'a>(text: String, mime: Mime) -> Entity<'a> {
+    Entity::Part(
+        Part::text("", text)
+            .with_mime(mime)
+            .with_transfer_encoding(TransferEncoding::QuotedPrintable),
+    )
+}
+
+/// An attachment, or inline part, which is base64 encoded.
+fn attachment<'a>(disposition: &str, filename: &str, mime: Mime, part: Part<'a>) -> Part<'a> {
+    // The parameter is always encoded as a valid header value.
+    let value = HeaderValue::from_str(&format!("{}; {}", disposition, filename_param(filename)))
+        .unwrap_or(HeaderValue::from_static("attachment"));
+
+    part.with_mime(mime)
+        .with_transfer_encoding(TransferEncoding::Base64)
+        .with_header(CONTENT_DISPOSITION, value)
+}
+
+/// Writes a filename parameter, which is encoded if it isn't ASCII.
+///
+/// [See RFC2231](https://tools.ietf.org/html/rfc2231#section-4).
+fn filename_param(filename: &str) -> String {
+    if filename.is_ascii() && !filename.chars().any(|c| c.is_ascii_control()) {
+        return format!(
+            "filename=\"{}\"",
+            filename.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+    }
+
+    let mut param = String::from("filename*=UTF-8''");
+
+    for b in filename.bytes() {
+        if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+            param.push(b as char);
+        } else {
+            let _ = write!(param, "%{:02X}", b);
+        }
+    }
+
+    param
+}
+
+/// Encodes text as RFC2047 encoded words, separated by folding whitespace.
+///
+/// [See](https://tools.ietf.org/html/rfc2047).
+fn encode_word(text: &str) -> String {
+    // Each encoded word is at most 75 characters, which fits 45 bytes of
+    // base64 input.
+    let mut words = vec![];
+    let mut start = 0;
+
+    while start < text.len() {
+        let mut end = (start + 45).min(text.len());
+
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        words.push(format!(
+            "=?UTF-8?B?{}?=",
+            STANDARD.encode(&text[start..end])
+        ));
+        start = end;
+    }
+
+    words.join(" ")
+}
+
+/// Folds a header, so that lines are at most 76 characters where possible,
+/// breaking lines between items.
+fn fold<'s, I>(prefix: &str, items: I, separator: &str) -> String
+where
+    I: IntoIterator<Item = &'s str>,
+{
+    let mut out = String::from(prefix);
+    let mut line_len = prefix.len();
+
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            if line_len + separator.len() + item.len() > MAX_LINE_LEN {
+                out.push_str(separator.trim_end());
+                out.push_str("\r\n ");
+                line_len = 1;
+            } else {
+                out.push_str(separator);
+                line_len += separator.len();
+            }
+        }
+        out.push_str(item);
+        line_len += item.len();
+    }
+
+    out
+}
+
+/// Returns true if a value can be written as it is, which is printable
+/// ASCII, and whitespace other than line breaks.
+fn is_plain(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b.is_ascii_graphic() || b == b' ' || b == b'\t')
+}
+
+/// Returns true if a folded header line has the given name.
+fn is_header(header: &str, name: &str) -> bool {
+    header
+        .split_once(':')
+        .is_some_and(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+}
+
+/// Formats a time as an RFC5322 date in UTC, like
+/// `Sun, 06 Nov 1994 08:49:37 +0000`.
+///
+/// [See RFC5322 3.3](https://tools.ietf.org/html/rfc5322#section-3.3).
+fn format_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = secs / 86400;
+    let secs = secs % 86400;
+    let (year, month, day) = civil_from_days(days as i64);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Converts all line endings to CRLF.
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+/// The stream of a message, starting with its headers.
+pub struct MessageBody<'a> {
+    /// The top level headers, until they are written.
+    headers: Option<BytesMut>,
+
+    body: Body<'a>,
+}
+
+impl<'a> Stream for MessageBody<'a> {
+    type Item = Result<BytesMut, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let message = self.get_mut();
+
+        match message.headers.take() {
+            Some(headers) => Poll::Ready(Some(Ok(headers))),
+            None => Pin::new(&mut message.body).poll_next(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_word, fold, format_date, Mailbox, Message};
+    use crate::parser;
+    use bytes::Bytes;
+    use futures_util::TryStreamExt;
+    use http::header::CONTENT_TYPE;
+    use std::{
+        io::Cursor,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn mailbox_encodes_names() {
+        assert_eq!(
+            Mailbox::new("a@example.com")
+                .unwrap()
+                .with_name("A \"B\"")
+                .to_string(),
+            "\"A \\\"B\\\"\" <a@example.com>"
+        );
+        assert_eq!(
+            Mailbox::new("a@example.com")
+                .unwrap()
+                .with_name("Zoë")
+                .to_string(),
+            "=?UTF-8?B?Wm/Dqw==?= <a@example.com>"
+        );
+    }
+
+    #[test]
+    fn mailbox_rejects_invalid_addresses() {
+        for address in [
+            "",
+            "a@example.com>",
+            "<a@example.com",
+            "a@example.com\r\nBcc: b@example.com",
+        ] {
+            let err = Mailbox::new(address).unwrap_err();
+
+            assert_eq!(err.kind(), "invalid_address", "{:?}", address);
+        }
+    }
+
+    #[test]
+    fn format_date_is_rfc5322() {
+        assert_eq!(
+            format_date(UNIX_EPOCH + Duration::from_secs(784111777)),
+            "Sun, 06 Nov 1994 08:49:37 +0000"
+        );
+        assert_eq!(
+            format_date(UNIX_EPOCH + Duration::from_secs(951782400)),
+            "Tue, 29 Feb 2000 00:00:00 +0000"
+        );
+    }
+
+    #[test]
+    fn fold_breaks_long_lines() {
+        let items = ["a".repeat(40), "b".repeat(40)];
+        let folded = fold("To: ", items.iter().map(String::as_str), ", ");
+
+        assert_eq!(
+            folded,
+            format!("To: {},\r\n {}", "a".repeat(40), "b".repeat(40))
+        );
+        assert!(encode_word(&"é".repeat(40))
+            .split(' ')
+            .all(|word| word.len() <= 75));
+    }
+
+    #[tokio::test]
+    async fn address_headers_keep_names_with_commas() {
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+
+        message.add_to(
+            Mailbox::new("jane@example.com")
+                .unwrap()
+                .with_name("Doe, Jane"),
+        );
+        message.add_to(Mailbox::new("b@example.com").unwrap());
+        message.add_cc(Mailbox::new("c@example.com").unwrap().with_name("C, D"));
+
+        let data = message.into_body().try_concat().await.unwrap().freeze();
+        let text = String::from_utf8(data.to_vec()).unwrap();
+        let (head, _) = text.split_once("\r\n\r\n").unwrap();
+        let headers = parser::parse_headers(head.as_bytes()).unwrap();
+
+        assert_eq!(
+            headers["to"],
+            "\"Doe, Jane\" <jane@example.com>, <b@example.com>"
+        );
+        assert_eq!(headers["cc"], "\"C, D\" <c@example.com>");
+    }
+
+    #[tokio::test]
+    async fn into_body_nests_multiparts() {
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+
+        message.add_to(Mailbox::new("a@example.com").unwrap());
+        message.add_to(Mailbox::new("b@example.com").unwrap());
+        message.set_subject("Café");
+        message.set_text("Hello\nWorld");
+        message.set_html("<img src=\"cid:logo\">");
+        message.add_inline("logo", mime::IMAGE_PNG, Cursor::new(vec![0u8; 4]));
+        message.add_attachment("résumé.txt", mime::TEXT_PLAIN, Cursor::new("Hello World!"));
+
+        let data = message.into_body().try_concat().await.unwrap().freeze();
+        let text = String::from_utf8(data.to_vec()).unwrap();
+        let (head, body) = text.split_once("\r\n\r\n").unwrap();
+        let headers = parser::parse_headers(head.as_bytes()).unwrap();
+
+        assert_eq!(headers["from"], "<from@example.com>");
+        assert_eq!(headers["to"], "<a@example.com>, <b@example.com>");
+        assert_eq!(headers["subject"], "=?UTF-8?B?Q2Fmw6k=?=");
+        assert_eq!(headers["mime-version"], "1.0");
+
+        let content_type = headers[CONTENT_TYPE].to_str().unwrap();
+        let boundary = parser::boundary(content_type).unwrap();
+        let mixed = parser::parse(&Bytes::from(body.to_string()), &boundary).unwrap();
+
+        assert!(content_type.starts_with("multipart/mixed"));
+        assert_eq!(mixed.len(), 2);
+        assert_eq!(
+            mixed[1].headers["content-disposition"],
+            "attachment; filename*=UTF-8''r%C3%A9sum%C3%A9.txt"
+        );
+        assert_eq!(mixed[1].headers["content-transfer-encoding"], "base64");
+        assert_eq!(mixed[1].body, "SGVsbG8gV29ybGQh");
+
+        let related_type = mixed[0].headers[CONTENT_TYPE].to_str().unwrap();
+        let related =
+            parser::parse(&mixed[0].body, &parser::boundary(related_type).unwrap()).unwrap();
+
+        assert!(related_type.starts_with("multipart/related"));
+        assert_eq!(related[1].headers["content-id"], "<logo>");
+        assert_eq!(
+            related[1].headers["content-disposition"],
+            "inline; filename=\"logo\""
+        );
+
+        let alternative_type = related[0].headers[CONTENT_TYPE].to_str().unwrap();
+        let alternative = parser::parse(
+            &related[0].body,
+            &parser::boundary(alternative_type).unwrap(),
+        )
+        .unwrap();
+
+        assert!(alternative_type.starts_with("multipart/alternative"));
+        assert_eq!(
+            alternative[0].headers[CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(alternative[0].body, "Hello\r\nWorld");
+        assert_eq!(
+            alternative[1].headers[CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(alternative[1].body, "<img src=3D\"cid:logo\">");
+    }
+
+    #[tokio::test]
+    async fn into_body_with_only_text_is_multipart() {
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+
+        message.set_text("Hello World!");
+
+        let data = message.into_body().try_concat().await.unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+
+        assert!(text.contains("Content-Type: multipart/mixed; boundary="));
+        assert!(text.contains("\r\n\r\nHello World!\r\n--"));
+    }
+
+    #[tokio::test]
+    async fn headers_with_line_breaks_are_encoded() {
+        let from = Mailbox::new("from@example.com")
+            .unwrap()
+            .with_name("A\r\nBcc: b@example.com");
+        let mut message = Message::new(from);
+
+        message.set_subject("Hi\r\nBcc: b@example.com");
+        message
+            .add_header("Reply-To", "c@example.com\nBcc: b@example.com")
+            .unwrap();
+        message.set_text("Hello World!");
+
+        let data = message.into_body().try_concat().await.unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+        let (head, _) = text.split_once("\r\n\r\n").unwrap();
+        let headers = parser::parse_headers(head.as_bytes()).unwrap();
+
+        assert!(!headers.contains_key("bcc"));
+        assert_eq!(
+            headers["subject"],
+            super::encode_word("Hi\r\nBcc: b@example.com")
+        );
+        assert_eq!(
+            message_err(Message::new(Mailbox::new("a@example.com").unwrap()), "Bcc:"),
+            "invalid_header_name"
+        );
+        assert_eq!(
+            message_err(
+                Message::new(Mailbox::new("a@example.com").unwrap()),
+                "X\r\nBcc"
+            ),
+            "invalid_header_name"
+        );
+    }
+
+    fn message_err(mut message: Message<'_>, name: &str) -> &'static str {
+        message
+            .add_header(name, "b@example.com")
+            .unwrap_err()
+            .kind()
+    }
+
+    #[tokio::test]
+    async fn set_subject_replaces_the_subject() {
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+
+        message.set_subject("First");
+        message.set_subject("Second");
+        message.set_text("Hello World!");
+
+        let data = message.into_body().try_concat().await.unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+
+        assert_eq!(text.matches("Subject:").count(), 1);
+        assert!(text.contains("Subject: Second\r\n"));
+    }
+
+    #[tokio::test]
+    async fn into_body_adds_a_date() {
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+
+        message.set_text("Hello World!");
+
+        let data = message.into_body().try_concat().await.unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+        let (head, _) = text.split_once("\r\n\r\n").unwrap();
+        let headers = parser::parse_headers(head.as_bytes()).unwrap();
+
+        assert!(headers["date"].to_str().unwrap().ends_with(" +0000"));
+
+        let mut message = Message::new(Mailbox::new("from@example.com").unwrap());
+        let date = "Sun, 06 Nov 1994 08:49:37 +0000";
+
+        message.add_header("Date", date).unwrap();
+        message.set_text("Hello World!");
+
+        let data = message.into_body().try_concat().await.unwrap();
+        let text = std::str::from_utf8(&data).unwrap();
+
+        assert_eq!(text.matches("Date:").count(), 1);
+        assert!(text.contains(&format!("Date: {}\r\n", date)));
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::error::Error;
+use std::time::Duration;
+
+/// Observes bodies as they are written, and forms as they are parsed, to
+/// export counters to a metrics system.
+///
+/// Every method does nothing by default, so an implementation only has to
+/// override the ones it records. `MetricsFacade` records all of them with
+/// the `metrics` crate, if the `metrics` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::client::multipart::{self, Metrics};
+/// use std::sync::{
+///     atomic::{AtomicU64, Ordering},
+///     Arc,
+/// };
+///
+/// #[derive(Default)]
+/// struct BytesWritten(AtomicU64);
+///
+/// impl Metrics for BytesWritten {
+///     fn bytes_written(&self, len: u64) {
+///         self.0.fetch_add(len, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut form = multipart::Form::default();
+///
+/// form.add_text("text", "Hello World!");
+/// form.set_metrics(Arc::new(BytesWritten::default()));
+/// ```
+pub trait Metrics: Send + Sync {
+    /// Called with the length of each chunk a body emits.
+    fn bytes_written(&self, len: u64) {
+        let _ = len;
+    }
+
+    /// Called once the content of a part has been fully written.
+    fn part_written(&self, name: &str, len: u64) {
+        let _ = (name, len);
+    }
+
+    /// Called with the total time a part's reader was pending, once the part
+    /// has been written, or its reader failed.
+    fn reader_pending(&self, name: &str, duration: Duration) {
+        let _ = (name, duration);
+    }
+
+    /// Called with each error a body returns.
+    fn write_error(&self, err: &Error) {
+        let _ = err;
+    }
+
+    /// Called with the length of each body that is parsed.
+    fn bytes_parsed(&self, len: u64) {
+        let _ = len;
+    }
+
+    /// Called for each field of a body that is parsed.
+    fn field_parsed(&self, name: &str, len: u64) {
+        let _ = (name, len);
+    }
+
+    /// Called with the error a body failed to parse with.
+    fn parse_error(&self, err: &Error) {
+        let _ = err;
+    }
+}
+
+/// Records metrics with the `metrics` crate, in whichever recorder is
+/// installed, like a Prometheus exporter.
+///
+/// | Metric                             | Type      | Labels         |
+/// |------------------------------------|-----------|----------------|
+/// | `multipart_bytes_written_total`    | counter   |                |
+/// | `multipart_parts_written_total`    | counter   |                |
+/// | `multipart_reader_pending_seconds` | histogram |                |
+/// | `multipart_bytes_parsed_total`     | counter   |                |
+/// | `multipart_fields_parsed_total`    | counter   |                |
+/// | `multipart_errors_total`           | counter   | `side`, `kind` |
+///
+/// `side` is `write`, or `parse`, and `kind` is the `Error::kind` of the
+/// error.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsFacade;
+
+#[cfg(feature = "metrics")]
+impl Metrics for MetricsFacade {
+    fn bytes_written(&self, len: u64) {
+        metrics::counter!("multipart_bytes_written_total").increment(len);
+    }
+
+    fn part_written(&self, _: &str, _: u64) {
+        metrics::counter!("multipart_parts_written_total").increment(1);
+    }
+
+    fn reader_pending(&self, _: &str, duration: Duration) {
+        metrics::histogram!("multipart_reader_pending_seconds").record(duration);
+    }
+
+    fn write_error(&self, err: &Error) {
+        metrics::counter!("multipart_errors_total", "side" => "write", "kind" => err.kind())
+            .increment(1);
+    }
+
+    fn bytes_parsed(&self, len: u64) {
+        metrics::counter!("multipart_bytes_parsed_total").increment(len);
+    }
+
+    fn field_parsed(&self, _: &str, _: u64) {
+        metrics::counter!("multipart_fields_parsed_total").increment(1);
+    }
+
+    fn parse_error(&self, err: &Error) {
+        metrics::counter!("multipart_errors_total", "side" => "parse", "kind" => err.kind())
+            .increment(1);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Metrics;
+    use crate::error::Error;
+    use std::{sync::Mutex, time::Duration};
+
+    /// Records what it observes, with the lengths of written chunks summed.
+    #[derive(Default)]
+    pub(crate) struct RecordingMetrics {
+        pub(crate) bytes: Mutex<u64>,
+
+        pub(crate) events: Mutex<Vec<String>>,
+    }
+
+    impl RecordingMetrics {
+        fn push(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    impl Metrics for RecordingMetrics {
+        fn bytes_written(&self, len: u64) {
+            *self.bytes.lock().unwrap() += len;
+        }
+
+        fn part_written(&self, name: &str, len: u64) {
+            self.push(format!("part_written {} {}", name, len));
+        }
+
+        fn reader_pending(&self, name: &str, _: Duration) {
+            self.push(format!("reader_pending {}", name));
+        }
+
+        fn write_error(&self, err: &Error) {
+            self.push(format!("write_error {}", err.kind()));
+        }
+
+        fn bytes_parsed(&self, len: u64) {
+            self.push(format!("bytes_parsed {}", len));
+        }
+
+        fn field_parsed(&self, name: &str, len: u64) {
+            self.push(format!("field_parsed {} {}", name, len));
+        }
+
+        fn parse_error(&self, err: &Error) {
+            self.push(format!("parse_error {}", err.kind()));
+        }
+    }
+}
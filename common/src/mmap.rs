@@ -0,0 +1,99 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Files that are written from a memory mapping, rather than read.
+
+use crate::error::Error;
+use bytes::Bytes;
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// The length of each chunk of a mapped file.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A file that is mapped into memory. Chunks are slices of the mapping, so
+/// they aren't copied.
+pub(crate) struct MappedFile {
+    /// The file, which is kept open to check its length.
+    file: File,
+
+    path: PathBuf,
+
+    /// The mapping, which lives as long as any chunk of it.
+    content: Bytes,
+
+    /// The length of the content that has been written.
+    pos: usize,
+}
+
+impl MappedFile {
+    /// Maps a file, if it is at least `min_len` bytes long. Returns `None`
+    /// if it is shorter, so it can be read instead.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated while the mapping, or any chunk of it
+    /// is alive. See `Form::map_files`.
+    pub(crate) unsafe fn open(path: &Path, min_len: u64) -> Result<Option<MappedFile>, Error> {
+        let file_open = |source| Error::FileOpen {
+            part: None,
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let file = File::open(path).map_err(file_open)?;
+        let len = file.metadata().map_err(file_open)?.len();
+
+        // An empty file can't be mapped on every platform.
+        if len == 0 || len < min_len {
+            return Ok(None);
+        }
+
+        let mmap = Mmap::map(&file).map_err(file_open)?;
+
+        Ok(Some(MappedFile {
+            file,
+            path: path.to_path_buf(),
+            content: Bytes::from_owner(mmap),
+            pos: 0,
+        }))
+    }
+
+    /// Returns the next chunk, or `None` once the whole file has been
+    /// written. Fails if the length of the file changed since it was mapped.
+    pub(crate) fn next_chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        let len = self
+            .file
+            .metadata()
+            .map_err(|source| Error::ContentRead { part: None, source })?
+            .len();
+
+        if len != self.content.len() as u64 {
+            return Err(Error::FileChanged {
+                part: None,
+                path: self.path.clone(),
+                len: self.content.len() as u64,
+                actual: len,
+            });
+        }
+
+        if self.pos == self.content.len() {
+            return Ok(None);
+        }
+
+        let end = self.content.len().min(self.pos + CHUNK_SIZE);
+        let chunk = self.content.slice(self.pos..end);
+
+        self.pos = end;
+
+        Ok(Some(chunk))
+    }
+}
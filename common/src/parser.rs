@@ -0,0 +1,215 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! A parser for multipart bodies that have been fully received.
+//!
+//! [See](https://tools.ietf.org/html/rfc2046#section-5.1.1).
+
+use crate::error::Error;
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_DISPOSITION};
+use memchr::memmem;
+use mime::Mime;
+
+/// A part of a multipart body.
+pub(crate) struct ParsedPart {
+    pub headers: HeaderMap,
+
+    pub body: Bytes,
+}
+
+impl ParsedPart {
+    /// Returns a parameter of the Content-Disposition header, like `name`,
+    /// or `filename`.
+    pub fn disposition_param(&self, param: &str) -> Option<String> {
+        let disposition = self.headers.get(&CONTENT_DISPOSITION)?.to_str().ok()?;
+
+        header_param(disposition, param)
+    }
+}
+
+/// Returns the boundary of a multipart Content-Type.
+pub(crate) fn boundary(content_type: &str) -> Result<String, Error> {
+    let mime: Mime = content_type
+        .parse()
+        .map_err(|_| Error::Parse(format!("invalid content type {:?}", content_type)))?;
+
+    if mime.type_() != mime::MULTIPART {
+        return Err(Error::Parse(format!(
+            "{} is not a multipart content type",
+            mime.essence_str()
+        )));
+    }
+
+    mime.get_param(mime::BOUNDARY)
+        .map(|boundary| boundary.as_str().to_string())
+        .ok_or_else(|| Error::Parse("missing boundary".to_string()))
+}
+
+/// Splits a multipart body into its parts. The preamble, and epilogue are
+/// ignored.
+pub(crate) fn parse(body: &Bytes, boundary: &str) -> Result<Vec<ParsedPart>, Error> {
+    let delimiter = format!("\r\n--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    // The first delimiter doesn't have to be preceded by a line break.
+    let mut pos = if body.starts_with(&delimiter[2..]) {
+        delimiter.len() - 2
+    } else {
+        find(body, delimiter, 0).ok_or_else(|| Error::Parse("missing boundary".to_string()))?
+            + delimiter.len()
+    };
+    let mut parts = vec![];
+
+    loop {
+        if body[pos..].starts_with(b"--") {
+            return Ok(parts);
+        }
+
+        // Skip any transport padding after the boundary.
+        let start = find(body, b"\r\n", pos)
+            .ok_or_else(|| Error::Parse("unterminated boundary".to_string()))?
+            + 2;
+        let end = find(body, delimiter, start)
+            .ok_or_else(|| Error::Parse("missing closing boundary".to_string()))?;
+
+        parts.push(parse_part(body.slice(start..end))?);
+
+        pos = end + delimiter.len();
+    }
+}
+
+/// Parses the headers, and body of a part.
+fn parse_part(part: Bytes) -> Result<ParsedPart, Error> {
+    let (head, body) = if part.starts_with(b"\r\n") {
+        (&part[..0], part.slice(2..))
+    } else {
+        let end = find(&part, b"\r\n\r\n", 0)
+            .ok_or_else(|| Error::Parse("unterminated part headers".to_string()))?;
+
+        (&part[..end], part.slice(end + 4..))
+    };
+
+    Ok(ParsedPart {
+        headers: parse_headers(head)?,
+        body,
+    })
+}
+
+/// Parses header lines, without the empty line that ends them.
+pub(crate) fn parse_headers(head: &[u8]) -> Result<HeaderMap, Error> {
+    let mut headers = HeaderMap::new();
+
+    for line in head.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let colon = line
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or_else(|| Error::Parse("invalid header line".to_string()))?;
+        let name = HeaderName::from_bytes(&line[..colon])
+            .map_err(|_| Error::Parse("invalid header name".to_string()))?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+            .map_err(|_| Error::Parse(format!("invalid value for header {}", name)))?;
+
+        headers.append(name, value);
+    }
+
+    Ok(headers)
+}
+
+/// Returns a parameter of a header value like `form-data; name="field"`.
+fn header_param(value: &str, param: &str) -> Option<String> {
+    let mut rest = value.split_once(';')?.1;
+
+    loop {
+        let (key, after) = rest.split_once('=')?;
+        let after = after.trim_start();
+
+        let (value, after) = if let Some(quoted) = after.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    c => value.push(c),
+                }
+            }
+
+            (value, &quoted[end..])
+        } else {
+            let end = after.find(';').unwrap_or(after.len());
+
+            (after[..end].trim_end().to_string(), &after[end..])
+        };
+
+        if key.trim().eq_ignore_ascii_case(param) {
+            return Some(value);
+        }
+
+        rest = after.split_once(';')?.1;
+    }
+}
+
+/// Returns the position of `needle` in `haystack`, starting from `from`.
+pub(crate) fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    memmem::find(haystack.get(from..)?, needle).map(|i| i + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boundary, header_param, parse};
+    use bytes::Bytes;
+
+    #[test]
+    fn parse_returns_parts() {
+        let body = Bytes::from_static(
+            b"preamble\r\n--b\r\n\
+              content-disposition: form-data; name=\"a\"\r\n\r\n\
+              1\r\n--b  \r\n\
+              \r\n\
+              two\r\nlines\r\n--b--\r\nepilogue",
+        );
+        let parts = parse(&body, "b").unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].disposition_param("name").unwrap(), "a");
+        assert_eq!(parts[0].body, "1");
+        assert!(parts[1].headers.is_empty());
+        assert_eq!(parts[1].body, "two\r\nlines");
+    }
+
+    #[test]
+    fn parse_rejects_truncated_body() {
+        let body = Bytes::from_static(b"--b\r\n\r\ncontent");
+
+        assert!(parse(&body, "b").is_err());
+    }
+
+    #[test]
+    fn header_param_handles_quoting() {
+        let value = r#"form-data; filename="a \"b\"; c"; name=field"#;
+
+        assert_eq!(header_param(value, "name").unwrap(), "field");
+        assert_eq!(header_param(value, "filename").unwrap(), r#"a "b"; c"#);
+        assert_eq!(
+            boundary("multipart/mixed; boundary=\"batch_1\"").unwrap(),
+            "batch_1"
+        );
+    }
+}
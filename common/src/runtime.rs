@@ -0,0 +1,185 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Adapters for reading through an async runtime instead of blocking the
+//! executor thread.
+
+use futures_util::io::AsyncRead;
+use std::fs::File;
+
+#[cfg(feature = "tokio")]
+pub(crate) use tokio_rt::BlockingOffload;
+
+#[cfg(feature = "tokio")]
+mod tokio_rt {
+    use bytes::{Buf, Bytes, BytesMut};
+    use futures_core::ready;
+    use futures_util::io::AsyncRead;
+    use std::{
+        io::{self, Read},
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::{io::ReadBuf, runtime::Handle, sync::mpsc};
+
+    /// The size of each chunk read on the blocking thread pool.
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    /// Converts a sync reader into one that is read on the blocking thread
+    /// pool. This is a function pointer so that it can only be created for
+    /// `'static` readers, but can be stored in a form with any lifetime.
+    type Spawn<'a> = fn(
+        Box<dyn 'a + Read + Send + Unpin>,
+        &Handle,
+        usize,
+    ) -> Box<dyn 'a + AsyncRead + Send + Unpin>;
+
+    /// Moves sync readers onto tokio's blocking thread pool.
+    pub(crate) struct BlockingOffload<'a> {
+        spawn: Spawn<'a>,
+
+        /// The number of chunks that can be read ahead of the body.
+        capacity: usize,
+    }
+
+    impl<'a> Clone for BlockingOffload<'a> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<'a> Copy for BlockingOffload<'a> {}
+
+    impl BlockingOffload<'static> {
+        pub(crate) fn new(capacity: usize) -> BlockingOffload<'static> {
+            BlockingOffload {
+                spawn: |read, handle, capacity| {
+                    Box::new(BlockingRead::spawn(read, handle, capacity))
+                },
+                capacity: capacity.max(1),
+            }
+        }
+    }
+
+    impl<'a> BlockingOffload<'a> {
+        /// Starts reading on the blocking thread pool of the current runtime.
+        pub(crate) fn spawn(
+            &self,
+            read: Box<dyn 'a + Read + Send + Unpin>,
+        ) -> io::Result<Box<dyn 'a + AsyncRead + Send + Unpin>> {
+            let handle = Handle::try_current().map_err(io::Error::other)?;
+
+            Ok((self.spawn)(read, &handle, self.capacity))
+        }
+    }
+
+    /// Receives chunks from a reader running on the blocking thread pool.
+    ///
+    /// Dropping it stops the reader after its current read.
+    struct BlockingRead {
+        rx: mpsc::Receiver<io::Result<Bytes>>,
+
+        /// The rest of the last chunk received.
+        chunk: Bytes,
+    }
+
+    impl BlockingRead {
+        fn spawn<R>(mut read: R, handle: &Handle, capacity: usize) -> BlockingRead
+        where
+            R: 'static + Read + Send,
+        {
+            let (tx, rx) = mpsc::channel(capacity);
+
+            handle.spawn_blocking(move || loop {
+                let mut buf = BytesMut::zeroed(CHUNK_SIZE);
+
+                let chunk = match read.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        buf.truncate(n);
+                        Ok(buf.freeze())
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let is_err = chunk.is_err();
+
+                // The body was dropped, or the reader failed.
+                if tx.blocking_send(chunk).is_err() || is_err {
+                    break;
+                }
+            });
+
+            BlockingRead {
+                rx,
+                chunk: Bytes::new(),
+            }
+        }
+    }
+
+    impl AsyncRead for BlockingRead {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+
+            while this.chunk.is_empty() {
+                match ready!(this.rx.poll_recv(cx)) {
+                    Some(Ok(chunk)) => this.chunk = chunk,
+                    Some(Err(e)) => return Poll::Ready(Err(e)),
+                    None => return Poll::Ready(Ok(0)),
+                }
+            }
+
+            let len = buf.len().min(this.chunk.len());
+
+            buf[..len].copy_from_slice(&this.chunk[..len]);
+            this.chunk.advance(len);
+
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    /// Implements `futures::io::AsyncRead` for a tokio reader.
+    pub(crate) struct Compat<R>(pub R);
+
+    impl<R> AsyncRead for Compat<R>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let mut buf = ReadBuf::new(buf);
+
+            ready!(Pin::new(&mut self.get_mut().0).poll_read(cx, &mut buf))?;
+
+            Poll::Ready(Ok(buf.filled().len()))
+        }
+    }
+}
+
+/// Wraps an opened file so reads are performed by the runtime's blocking
+/// thread pool. If both runtimes are enabled, tokio is used.
+///
+/// A tokio file must be read from within a tokio runtime.
+#[cfg(feature = "tokio")]
+pub(crate) fn async_file(file: File) -> Box<dyn AsyncRead + Send + Unpin> {
+    Box::new(tokio_rt::Compat(tokio::fs::File::from_std(file)))
+}
+
+/// Wraps an opened file so reads are performed by the runtime's blocking
+/// thread pool.
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) fn async_file(file: File) -> Box<dyn AsyncRead + Send + Unpin> {
+    Box::new(async_std::fs::File::from(file))
+}
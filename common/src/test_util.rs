@@ -0,0 +1,178 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! This module contains utilities for testing code that builds forms.
+
+use crate::{boundary::BoundaryGenerator, parser};
+use bytes::Bytes;
+use rand::{distributions::Alphanumeric, rngs::SmallRng, Rng, SeedableRng};
+use std::fmt::Write;
+
+/// A boundary generator that always generates `boundary`.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::{client::multipart, test_util::FixedBoundary};
+///
+/// let form = multipart::Form::new::<FixedBoundary>();
+///
+/// assert_eq!(form.content_type(), "multipart/form-data; boundary=boundary");
+/// ```
+pub struct FixedBoundary;
+
+impl BoundaryGenerator for FixedBoundary {
+    fn generate_boundary() -> String {
+        "boundary".to_string()
+    }
+}
+
+/// A boundary generator that generates the same random looking boundary
+/// for the same seed.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::{client::multipart, test_util::SeededBoundary};
+///
+/// let a = multipart::Form::new::<SeededBoundary<42>>();
+/// let b = multipart::Form::new::<SeededBoundary<42>>();
+///
+/// assert_eq!(a.content_type(), b.content_type());
+/// ```
+pub struct SeededBoundary<const SEED: u64>;
+
+impl<const SEED: u64> BoundaryGenerator for SeededBoundary<SEED> {
+    fn generate_boundary() -> String {
+        let rng = SmallRng::seed_from_u64(SEED);
+        let ascii = rng.sample_iter(&Alphanumeric);
+
+        String::from_iter(ascii.map(|b| b as char).take(6))
+    }
+}
+
+/// Prints a multipart body so it reads well in a diff. Each part is printed
+/// with its headers, and content, and the boundary is left out, so bodies
+/// with different boundaries print the same.
+///
+/// Line breaks in the content are printed as new lines. Other control
+/// characters, and bytes that aren't UTF-8 are escaped, and a line feed that
+/// isn't part of a CRLF is printed as `\n`, followed by a new line.
+///
+/// A body that can't be parsed is printed escaped.
+///
+/// # Examples
+///
+/// ```
+/// use common_multipart_rfc7578::test_util::pretty_print;
+///
+/// let body = b"--b\r\ncontent-type: text/plain\r\n\r\nHello\r\nWorld\r\n--b--\r\n";
+///
+/// assert_eq!(
+///     pretty_print("multipart/form-data; boundary=b", body),
+///     "--- part 0\n\
+///      content-type: text/plain\n\
+///      \n\
+///      Hello\n\
+///      World\n\
+///      --- end\n"
+/// );
+/// ```
+pub fn pretty_print(content_type: &str, body: &[u8]) -> String {
+    let body = Bytes::copy_from_slice(body);
+    let parts =
+        match parser::boundary(content_type).and_then(|boundary| parser::parse(&body, &boundary)) {
+            Ok(parts) => parts,
+            Err(e) => return format!("--- unparsed: {}\n{}\n", e, escape(&body)),
+        };
+
+    let mut out = String::new();
+
+    for (i, part) in parts.iter().enumerate() {
+        let _ = writeln!(out, "--- part {}", i);
+        for (name, value) in part.headers.iter() {
+            let _ = writeln!(out, "{}: {}", name, escape(value.as_bytes()));
+        }
+        out.push('\n');
+        out.push_str(&escape(&part.body));
+        if !part.body.is_empty() {
+            out.push('\n');
+        }
+    }
+    out.push_str("--- end\n");
+
+    out
+}
+
+/// Escapes content, keeping CRLF line breaks as new lines.
+fn escape(content: &[u8]) -> String {
+    let mut out = String::new();
+
+    for chunk in content.utf8_chunks() {
+        let mut chars = chunk.valid().chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    out.push('\n');
+                }
+                '\n' => out.push_str("\\n\n"),
+                '\t' => out.push('\t'),
+                c if c.is_control() => out.extend(c.escape_default()),
+                c => out.push(c),
+            }
+        }
+        for b in chunk.invalid() {
+            let _ = write!(out, "\\x{:02x}", b);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pretty_print, FixedBoundary, SeededBoundary};
+    use crate::{boundary::BoundaryGenerator, client_::Form};
+    use std::io::Cursor;
+
+    #[test]
+    fn seeded_boundary_depends_on_seed() {
+        assert_eq!(
+            SeededBoundary::<1>::generate_boundary(),
+            SeededBoundary::<1>::generate_boundary()
+        );
+        assert_ne!(
+            SeededBoundary::<1>::generate_boundary(),
+            SeededBoundary::<2>::generate_boundary()
+        );
+    }
+
+    #[tokio::test]
+    async fn pretty_print_escapes_content() {
+        let mut form = Form::new::<FixedBoundary>();
+
+        form.add_reader("input", Cursor::new(b"a\nb\r\n\x00\xff".to_vec()));
+
+        let content_type = form.content_type();
+        let body = form.encode_to_vec().await.unwrap();
+
+        assert_eq!(
+            pretty_print(&content_type, &body),
+            "--- part 0\n\
+             content-type: application/octet-stream\n\
+             content-disposition: form-data; name=\"input\"\n\
+             \n\
+             a\\n\n\
+             b\n\
+             \\u{0}\\xff\n\
+             --- end\n"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// A future that completes once a delay has passed.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The clock a `RateLimiter` waits on, so that it can be used with any async
+/// runtime.
+///
+/// Implementations are available for tokio, and async-std with the
+/// corresponding features.
+pub trait Timer: Send + Sync {
+    /// Returns a future that completes after the duration.
+    fn sleep(&self, duration: Duration) -> Sleep;
+
+    /// Returns the current time. Timers with a clock that can be paused in
+    /// tests should return its time.
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A timer on tokio's clock.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+/// A timer on async-std's clock.
+#[cfg(feature = "async-std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncStdTimer;
+
+#[cfg(feature = "async-std")]
+impl Timer for AsyncStdTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// Limits the rate bodies are written at with a token bucket, which allows
+/// a burst of bytes to be written at once, and then refills at the rate.
+///
+/// Clones share the same bucket, so one limiter can cap the total rate of
+/// several bodies written at the same time.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # {
+/// use common_multipart_rfc7578::client::multipart::{self, RateLimiter, TokioTimer};
+///
+/// // 64 KiB per second, with bursts of up to 16 KiB, shared by both forms.
+/// let limiter = RateLimiter::new(64 * 1024, 16 * 1024, TokioTimer);
+///
+/// let mut logs = multipart::Form::default();
+/// let mut metrics = multipart::Form::default();
+///
+/// logs.set_rate_limiter(limiter.clone());
+/// metrics.set_rate_limiter(limiter);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    /// The number of bytes added to the bucket every second.
+    rate: f64,
+
+    /// The size of the bucket.
+    burst: f64,
+
+    timer: Box<dyn Timer>,
+
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// The number of bytes that can be written now. This is negative if a
+    /// chunk larger than the bucket was written.
+    tokens: f64,
+
+    /// When the tokens were last refilled.
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter that allows `bytes_per_second` on average, and
+    /// bursts of up to `burst` bytes. The bucket starts full.
+    pub fn new<T>(bytes_per_second: u64, burst: u64, timer: T) -> RateLimiter
+    where
+        T: 'static + Timer,
+    {
+        let burst = burst.max(1) as f64;
+
+        RateLimiter {
+            inner: Arc::new(Shared {
+                rate: bytes_per_second.max(1) as f64,
+                burst,
+                bucket: Mutex::new(Bucket {
+                    tokens: burst,
+                    updated: timer.now(),
+                }),
+                timer: Box::new(timer),
+            }),
+        }
+    }
+
+    /// Takes tokens for a chunk, if there are enough to write it. Otherwise
+    /// returns how long to wait for them.
+    ///
+    /// A chunk larger than the bucket only waits for a full bucket, and
+    /// leaves the bucket in debt.
+    pub(crate) fn acquire(&self, len: usize) -> Option<Duration> {
+        if len == 0 {
+            return None;
+        }
+
+        let shared = &self.inner;
+        let now = shared.timer.now();
+        let mut bucket = shared.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * shared.rate).min(shared.burst);
+        bucket.updated = now;
+
+        let required = (len as f64).min(shared.burst);
+
+        if bucket.tokens >= required {
+            bucket.tokens -= len as f64;
+
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (required - bucket.tokens) / shared.rate,
+            ))
+        }
+    }
+
+    /// Waits on the limiter's timer.
+    pub(crate) fn sleep(&self, duration: Duration) -> Sleep {
+        self.inner.timer.sleep(duration)
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("rate", &self.inner.rate)
+            .field("burst", &self.inner.burst)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{RateLimiter, Sleep, Timer};
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    /// A clock that only moves when something sleeps on it.
+    #[derive(Clone)]
+    pub(crate) struct ManualTimer {
+        pub(crate) now: Arc<Mutex<Instant>>,
+    }
+
+    impl ManualTimer {
+        pub(crate) fn new() -> ManualTimer {
+            ManualTimer {
+                now: Arc::new(Mutex::new(Instant::now())),
+            }
+        }
+
+        pub(crate) fn elapsed(&self, start: Instant) -> Duration {
+            *self.now.lock().unwrap() - start
+        }
+    }
+
+    impl Timer for ManualTimer {
+        fn sleep(&self, duration: Duration) -> Sleep {
+            *self.now.lock().unwrap() += duration;
+
+            Box::pin(std::future::ready(()))
+        }
+
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn acquire_waits_for_tokens() {
+        let timer = ManualTimer::new();
+        let limiter = RateLimiter::new(100, 50, timer.clone());
+
+        assert_eq!(limiter.acquire(50), None);
+        assert_eq!(limiter.acquire(10), Some(Duration::from_millis(100)));
+
+        *timer.now.lock().unwrap() += Duration::from_millis(100);
+
+        assert_eq!(limiter.acquire(10), None);
+        // Larger than the bucket, so it waits for a full bucket.
+        assert_eq!(limiter.acquire(200), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn clones_share_the_bucket() {
+        let limiter = RateLimiter::new(100, 100, ManualTimer::new());
+        let other = limiter.clone();
+
+        assert_eq!(limiter.acquire(60), None);
+        assert!(other.acquire(60).is_some());
+    }
+}
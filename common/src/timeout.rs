@@ -0,0 +1,95 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::throttle::{Sleep, Timer};
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Timeouts that stop a body from waiting forever on a reader that hangs,
+/// like a slow upstream response piped into a form.
+///
+/// A body that times out returns `Error::Timeout`, and drops its readers.
+/// The timeouts wait on a `Timer`, so they can be used with any async
+/// runtime.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # {
+/// use common_multipart_rfc7578::client::multipart::{self, Timeouts, TokioTimer};
+/// use std::time::Duration;
+///
+/// let mut form = multipart::Form::default();
+///
+/// form.set_timeouts(
+///     Timeouts::new(TokioTimer)
+///         .with_idle(Duration::from_secs(30))
+///         .with_total(Duration::from_secs(300)),
+/// );
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Timeouts {
+    timer: Arc<dyn Timer>,
+
+    idle: Option<Duration>,
+
+    total: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Creates timeouts on a timer. Neither timeout is set.
+    pub fn new<T>(timer: T) -> Timeouts
+    where
+        T: 'static + Timer,
+    {
+        Timeouts {
+            timer: Arc::new(timer),
+            idle: None,
+            total: None,
+        }
+    }
+
+    /// Fails the body if a part's reader is pending for longer than the
+    /// timeout without producing any content.
+    pub fn with_idle(mut self, timeout: Duration) -> Timeouts {
+        self.idle = Some(timeout);
+        self
+    }
+
+    /// Fails the body if it isn't fully written within the timeout of being
+    /// polled for the first time.
+    pub fn with_total(mut self, timeout: Duration) -> Timeouts {
+        self.total = Some(timeout);
+        self
+    }
+
+    /// Returns the idle timeout, if set.
+    pub fn idle(&self) -> Option<Duration> {
+        self.idle
+    }
+
+    /// Returns the total timeout, if set.
+    pub fn total(&self) -> Option<Duration> {
+        self.total
+    }
+
+    /// Waits on the timer.
+    pub(crate) fn sleep(&self, duration: Duration) -> Sleep {
+        self.timer.sleep(duration)
+    }
+}
+
+impl fmt::Debug for Timeouts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeouts")
+            .field("idle", &self.idle)
+            .field("total", &self.total)
+            .finish()
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+//! Spans, and events for bodies, and their parts, if the `tracing` feature is
+//! enabled. Otherwise everything here does nothing, and takes no space.
+
+use crate::{client_::Part, error::Error};
+
+#[cfg(feature = "tracing")]
+pub(crate) use enabled::{BodyTrace, PartTrace};
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use disabled::{BodyTrace, PartTrace};
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use super::{Error, Part};
+    use std::time::{Duration, Instant};
+    use tracing::{field, Span};
+
+    /// How long a reader can wait before it is reported as stalled.
+    const STALL_THRESHOLD: Duration = Duration::from_secs(5);
+
+    /// The span of a body, which covers every poll until it is written.
+    pub(crate) struct BodyTrace {
+        span: Span,
+
+        started: Option<Instant>,
+    }
+
+    impl BodyTrace {
+        pub(crate) fn new(content_type: &str, parts: usize) -> BodyTrace {
+            BodyTrace {
+                span: tracing::info_span!(
+                    "multipart_body",
+                    content_type,
+                    parts,
+                    bytes = field::Empty,
+                    duration_ms = field::Empty,
+                ),
+                started: None,
+            }
+        }
+
+        pub(crate) fn span(&self) -> &Span {
+            &self.span
+        }
+
+        /// Starts the clock on the first poll, rather than when the body
+        /// is created.
+        pub(crate) fn polled(&mut self) {
+            self.started.get_or_insert_with(Instant::now);
+        }
+
+        pub(crate) fn part(&self, index: usize, part: &Part<'_>) -> PartTrace {
+            PartTrace {
+                span: tracing::debug_span!(
+                    parent: &self.span,
+                    "multipart_part",
+                    index,
+                    name = part.name(),
+                    filename = part.filename(),
+                    content_type = %part.content_type(),
+                    bytes = field::Empty,
+                    duration_ms = field::Empty,
+                ),
+                started: Instant::now(),
+                pending_since: None,
+                stall_reported: false,
+            }
+        }
+
+        pub(crate) fn finished(&self, bytes: u64) {
+            let duration_ms = elapsed_ms(self.started);
+
+            self.span.record("bytes", bytes);
+            self.span.record("duration_ms", duration_ms);
+            tracing::info!(parent: &self.span, bytes, duration_ms, "body written");
+        }
+
+        pub(crate) fn failed(&self, err: &Error) {
+            tracing::warn!(parent: &self.span, error = %err, "body failed");
+        }
+    }
+
+    /// The span of a part, from its boundary until its content is written.
+    pub(crate) struct PartTrace {
+        span: Span,
+
+        started: Instant,
+
+        /// When the reader started waiting, if it is.
+        pending_since: Option<Instant>,
+
+        /// Whether the current stall has been reported.
+        stall_reported: bool,
+    }
+
+    impl PartTrace {
+        /// Reports a reader that is still waiting once it has stalled, so a
+        /// reader that never resumes is reported too.
+        pub(crate) fn pending(&mut self) {
+            let since = *self.pending_since.get_or_insert_with(Instant::now);
+
+            self.report_stall(since);
+        }
+
+        pub(crate) fn ready(&mut self) {
+            if let Some(since) = self.pending_since.take() {
+                self.report_stall(since);
+            }
+            self.stall_reported = false;
+        }
+
+        /// Reports a stall once, if the reader has waited long enough.
+        fn report_stall(&mut self, since: Instant) {
+            let stalled = since.elapsed();
+
+            if !self.stall_reported && stalled >= STALL_THRESHOLD {
+                self.stall_reported = true;
+                tracing::warn!(
+                    parent: &self.span,
+                    stalled_ms = stalled.as_millis() as u64,
+                    "reader stalled"
+                );
+            }
+        }
+
+        pub(crate) fn finished(&self, bytes: u64) {
+            let duration_ms = self.started.elapsed().as_millis() as u64;
+
+            self.span.record("bytes", bytes);
+            self.span.record("duration_ms", duration_ms);
+            tracing::debug!(parent: &self.span, bytes, duration_ms, "part written");
+        }
+
+        pub(crate) fn failed(&self, offset: u64, err: &Error) {
+            tracing::warn!(parent: &self.span, offset, error = %err, "reader failed");
+        }
+    }
+
+    fn elapsed_ms(started: Option<Instant>) -> u64 {
+        started.map_or(0, |started| started.elapsed().as_millis() as u64)
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod disabled {
+    use super::{Error, Part};
+
+    pub(crate) struct BodyTrace;
+
+    impl BodyTrace {
+        #[inline]
+        pub(crate) fn new(_: &str, _: usize) -> BodyTrace {
+            BodyTrace
+        }
+
+        #[inline]
+        pub(crate) fn polled(&mut self) {}
+
+        #[inline]
+        pub(crate) fn part(&self, _: usize, _: &Part<'_>) -> PartTrace {
+            PartTrace
+        }
+
+        #[inline]
+        pub(crate) fn finished(&self, _: u64) {}
+
+        #[inline]
+        pub(crate) fn failed(&self, _: &Error) {}
+    }
+
+    pub(crate) struct PartTrace;
+
+    impl PartTrace {
+        #[inline]
+        pub(crate) fn pending(&mut self) {}
+
+        #[inline]
+        pub(crate) fn ready(&mut self) {}
+
+        #[inline]
+        pub(crate) fn finished(&self, _: u64) {}
+
+        #[inline]
+        pub(crate) fn failed(&self, _: u64, _: &Error) {}
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2026 rust-multipart-rfc7578 Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+//
+
+use crate::{charset::Charset, client_::Part, error::Error};
+use std::fmt::Write;
+
+/// The Content-Type of a urlencoded form.
+pub(crate) const CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// How a form is serialized.
+///
+/// [See the URL Standard](https://url.spec.whatwg.org/#application/x-www-form-urlencoded).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FormEncoding {
+    /// `multipart/form-data`, or the subtype set with `Form::set_subtype`.
+    #[default]
+    Multipart,
+
+    /// `application/x-www-form-urlencoded`, which can only send `text/plain`
+    /// text parts. `Form::set_body` fails if the form has other parts, and
+    /// so does the body.
+    UrlEncoded,
+
+    /// `application/x-www-form-urlencoded` if the form only has text parts,
+    /// and is a `form-data` form, otherwise multipart.
+    Auto,
+}
+
+/// Serializes text parts with the urlencoded serializer of the URL Standard.
+/// Text is encoded with the charset first, and the name of the charset is
+/// sent as the first `_charset_` field if it is set.
+///
+/// [See the URL Standard](https://url.spec.whatwg.org/#urlencoded-serializing).
+pub(crate) fn encode<'p, 'a: 'p, I>(parts: I, charset: Option<Charset>) -> Result<String, Error>
+where
+    I: IntoIterator<Item = &'p Part<'a>>,
+{
+    let mut out = String::new();
+
+    if let Some(charset) = charset {
+        write_pair(&mut out, "_charset_".as_bytes(), charset.name().as_bytes());
+    }
+    for part in parts {
+        let text = part
+            .urlencoded_text()
+            .ok_or_else(|| Error::NotUrlEncodable {
+                name: part.name().to_string(),
+            })?;
+        let charset = charset.unwrap_or_default();
+
+        write_pair(
+            &mut out,
+            &charset.encode(part.name()),
+            &charset.encode(text),
+        );
+    }
+
+    Ok(out)
+}
+
+fn write_pair(out: &mut String, name: &[u8], value: &[u8]) {
+    if !out.is_empty() {
+        out.push('&');
+    }
+    write_component(out, name);
+    out.push('=');
+    write_component(out, value);
+}
+
+fn write_component(out: &mut String, input: &[u8]) {
+    for &b in input {
+        match b {
+            b'*' | b'-' | b'.' | b'_' => out.push(b as char),
+            b if b.is_ascii_alphanumeric() => out.push(b as char),
+            b' ' => out.push('+'),
+            b => {
+                let _ = write!(out, "%{:02X}", b);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::{charset::Charset, client_::Part, error::Error};
+    use std::io::Cursor;
+
+    #[test]
+    fn encode_escapes_text() {
+        let parts = [Part::text("a b", "x+y=z&w"), Part::text("emoji", "é*-._~")];
+
+        assert_eq!(
+            encode(&parts, None).unwrap(),
+            "a+b=x%2By%3Dz%26w&emoji=%C3%A9*-._%7E"
+        );
+        assert_eq!(
+            encode(&parts[..1], Some(Charset::UTF_8)).unwrap(),
+            "_charset_=UTF-8&a+b=x%2By%3Dz%26w"
+        );
+    }
+
+    #[test]
+    fn encode_rejects_other_parts() {
+        let parts = [
+            Part::text("text", "Hello"),
+            Part::reader("input", Cursor::new("Hello")),
+        ];
+
+        match encode(&parts, None) {
+            Err(Error::NotUrlEncodable { name }) => assert_eq!(name, "input"),
+            _ => panic!("expected an error"),
+        }
+    }
+}
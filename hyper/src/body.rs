@@ -8,17 +8,33 @@
 
 use crate::common_multipart::client::{multipart, Error};
 use bytes::Bytes;
-use futures_core::{ready, Stream};
+use futures_core::ready;
 use hyper::body::Frame;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-pub struct Body(multipart::Body<'static>);
+pub struct Body {
+    inner: multipart::Body<'static>,
+
+    /// Set once the trailers have been sent, or there were none to send.
+    finished: bool,
+}
+
+impl Body {
+    /// Returns the checksums that have been computed over the parts written
+    /// so far.
+    pub fn checksums(&self) -> &[multipart::PartChecksum] {
+        self.inner.checksums()
+    }
+}
 
 impl From<multipart::Body<'static>> for Body {
     #[inline]
     fn from(body: multipart::Body<'static>) -> Self {
-        Body(body)
+        Body {
+            inner: body,
+            finished: false,
+        }
     }
 }
 
@@ -32,12 +48,229 @@ impl hyper::body::Body for Body {
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
-        let Body(inner) = Pin::into_inner(self);
+        let body = Pin::into_inner(self);
+
+        if body.finished {
+            return Poll::Ready(None);
+        }
 
-        match ready!(Pin::new(inner).poll_next(cx)) {
-            Some(Ok(read)) => Poll::Ready(Some(Ok(Frame::data(read.freeze())))),
+        #[cfg(feature = "tracing")]
+        let span = body.inner.span().clone();
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        match ready!(body.inner.poll_bytes(cx)) {
+            Some(Ok(read)) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(len = read.len(), "data frame");
+
+                Poll::Ready(Some(Ok(Frame::data(read))))
+            }
             Some(Err(e)) => Poll::Ready(Some(Err(e))),
-            None => Poll::Ready(None),
+            None => {
+                // Trailers are sent after all of the data, once they are
+                // complete.
+                body.finished = true;
+
+                if body.inner.trailers().is_empty() {
+                    Poll::Ready(None)
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(count = body.inner.trailers().len(), "trailers frame");
+
+                    Poll::Ready(Some(Ok(Frame::trailers(body.inner.trailers().clone()))))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::multipart::{
+        self, Chunk, ChunkField, ChunkedUpload, Form, ParsedForm, SendFuture, Transport,
+    };
+    use bytes::Bytes;
+    use futures_util::io::Cursor;
+    use http_body_util::{BodyExt, Full};
+    use hyper::{
+        body::Incoming,
+        header::{HeaderName, HeaderValue, CONTENT_RANGE, CONTENT_TYPE, TRAILER},
+        server::conn::http1,
+        service::service_fn,
+        Request, Response, StatusCode,
+    };
+    use hyper_util::{
+        client::legacy::{connect::HttpConnector, Client},
+        rt::{TokioExecutor, TokioIo},
+    };
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+    use tokio::net::TcpListener;
+
+    /// What the server has received.
+    #[derive(Default)]
+    struct Received {
+        content: Vec<u8>,
+
+        /// Set once the first attempt at the second chunk has been refused.
+        refused: bool,
+    }
+
+    /// Appends each chunk to the content, if it starts where the content
+    /// ends. The first attempt at the second chunk is refused.
+    async fn receive(
+        req: Request<Incoming>,
+        received: Arc<Mutex<Received>>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        let status = |status| {
+            let mut res = Response::new(Full::default());
+
+            *res.status_mut() = status;
+
+            Ok(res)
+        };
+
+        let content_type = req.headers()[CONTENT_TYPE].to_str().unwrap().to_string();
+        let range = req.headers()[CONTENT_RANGE].to_str().unwrap().to_string();
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        let form = ParsedForm::parse(&content_type, body).unwrap();
+        let index = form.text("index").unwrap().unwrap();
+        let mut received = received.lock().unwrap();
+
+        if index == "1" && !received.refused {
+            received.refused = true;
+
+            return status(StatusCode::SERVICE_UNAVAILABLE);
+        }
+
+        let start = range["bytes ".len()..].split('-').next().unwrap();
+
+        if start.parse::<usize>().unwrap() != received.content.len() {
+            return status(StatusCode::CONFLICT);
+        }
+        received
+            .content
+            .extend_from_slice(form.field("file").unwrap().content());
+
+        status(StatusCode::OK)
+    }
+
+    struct HyperTransport {
+        client: Client<HttpConnector, multipart::Body>,
+
+        uri: String,
+    }
+
+    impl Transport for HyperTransport {
+        fn send(&mut self, chunk: Chunk, form: Form<'static>) -> SendFuture<'_> {
+            Box::pin(async move {
+                let mut req = Request::post(&self.uri);
+
+                if let Some(range) = chunk.content_range() {
+                    req = req.header(CONTENT_RANGE, range);
+                }
+                let res = self
+                    .client
+                    .request(form.set_body::<multipart::Body>(req)?)
+                    .await?;
+
+                if !res.status().is_success() {
+                    return Err(format!("chunk refused with {}", res.status()).into());
+                }
+
+                Ok(())
+            })
+        }
+    }
+
+    /// Builds a form with a static trailer, and a trailer computed from the
+    /// length of the body.
+    fn form_with_trailers() -> Form<'static> {
+        let mut form = Form::default();
+
+        form.add_text("text", "Hello World!");
+        form.add_async_reader("input", Cursor::new(vec![b'a'; 100_000]));
+        form.add_trailer(
+            HeaderName::from_static("x-upload-source"),
+            HeaderValue::from_static("test"),
+        );
+        form.add_computed_trailer(HeaderName::from_static("x-upload-length"), |body| {
+            HeaderValue::from(body.bytes_written())
+        });
+
+        form
+    }
+
+    #[tokio::test]
+    async fn trailers_are_sent_after_the_data() {
+        let req = form_with_trailers()
+            .set_body::<multipart::Body>(Request::post("http://localhost/upload"))
+            .unwrap();
+
+        assert_eq!(req.headers()[TRAILER], "x-upload-source, x-upload-length");
+
+        let collected = req.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().unwrap().clone();
+        let data = collected.to_bytes();
+
+        assert_eq!(trailers["x-upload-source"], "test");
+        assert_eq!(trailers["x-upload-length"], data.len().to_string());
+
+        let mut body = form_with_trailers()
+            .set_body::<multipart::Body>(Request::post("http://localhost/upload"))
+            .unwrap()
+            .into_body();
+        let mut frames = vec![];
+
+        while let Some(frame) = body.frame().await {
+            frames.push(frame.unwrap());
         }
+
+        let (last, data) = frames.split_last().unwrap();
+
+        assert!(data.len() > 1);
+        assert!(data.iter().all(|frame| frame.is_data()));
+        assert_eq!(last.trailers_ref(), Some(&trailers));
+    }
+
+    #[tokio::test]
+    async fn chunked_upload_resumes_against_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received = Arc::new(Mutex::new(Received::default()));
+        let server_received = received.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let received = server_received.clone();
+
+                tokio::spawn(http1::Builder::new().serve_connection(
+                    TokioIo::new(socket),
+                    service_fn(move |req| receive(req, received.clone())),
+                ));
+            }
+        });
+
+        let content: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+        let mut transport = HyperTransport {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            uri: format!("http://{}/upload", addr),
+        };
+        let mut upload = ChunkedUpload::new(Cursor::new(content.clone()), 10_000)
+            .with_chunk_size(4096)
+            .with_filename("data.bin")
+            .with_field("index", ChunkField::Index);
+
+        assert!(upload.upload(&mut transport).await.is_err());
+        assert_eq!(upload.offset(), 4096);
+
+        upload.upload(&mut transport).await.unwrap();
+
+        assert!(upload.is_complete());
+        assert_eq!(received.lock().unwrap().content, content);
     }
 }
@@ -94,11 +94,35 @@ use common_multipart_rfc7578 as common_multipart;
 
 mod body;
 
+#[cfg(feature = "mime-message")]
+pub use crate::common_multipart::mime_message;
+#[cfg(feature = "test-util")]
+pub use crate::common_multipart::test_util;
+
 pub mod client {
-    pub use crate::common_multipart::client::Error;
+    pub use crate::common_multipart::client::{Error, Limit, PartContext, Timeout};
 
     pub mod multipart {
         pub use crate::body::Body;
-        pub use crate::common_multipart::client::multipart::{BoundaryGenerator, Form};
+        #[cfg(feature = "async-std")]
+        pub use crate::common_multipart::client::multipart::AsyncStdTimer;
+        #[cfg(feature = "metrics")]
+        pub use crate::common_multipart::client::multipart::MetricsFacade;
+        #[cfg(feature = "tokio")]
+        pub use crate::common_multipart::client::multipart::TokioTimer;
+        pub use crate::common_multipart::client::multipart::{
+            AbortHandle, AbortReport, Batch, BoundaryGenerator, Charset, Checksum,
+            ChecksumAlgorithm, Chunk, ChunkField, ChunkedUpload, Compression, DigestFormat, Field,
+            Form, FormEncoding, Metrics, ParsedForm, Part, PartChecksum, RateLimiter, SendFuture,
+            Sleep, Timeouts, Timer, TransferEncoding, Transport,
+        };
+        #[cfg(feature = "s3")]
+        pub use crate::common_multipart::client::multipart::{
+            Credentials, PolicyCondition, PostPolicy,
+        };
+        #[cfg(feature = "directory")]
+        pub use crate::common_multipart::client::multipart::{DirectoryOptions, SymlinkPolicy};
+        #[cfg(feature = "graphql")]
+        pub use crate::common_multipart::client::multipart::{GraphqlForm, GraphqlRequest, Upload};
     }
 }